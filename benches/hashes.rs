@@ -0,0 +1,100 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use hrpph_ict::hashes::HRPPHICT;
+use num_bigint::BigInt;
+
+const THRESHOLD: u16 = 10000;
+const LAMBDA: u64 = 2048;
+
+fn small_inputs(c: &mut Criterion) {
+    let generator = HRPPHICT::new(THRESHOLD, LAMBDA);
+    let x: i32 = 9876;
+    let big_x = BigInt::from(x);
+
+    let mut group = c.benchmark_group("small_inputs");
+    group.bench_function("hash", |b| b.iter(|| generator.hash(black_box(&big_x))));
+    group.bench_function("hash_small", |b| {
+        b.iter(|| generator.hash_small(black_box(x)))
+    });
+    group.finish();
+}
+
+fn negative_inputs(c: &mut Criterion) {
+    let generator = HRPPHICT::new(THRESHOLD, LAMBDA);
+    let small_x = BigInt::from(-9876);
+    let big_x = -(BigInt::from(1) << 1024u32);
+    let positive_big_x = -&big_x;
+
+    let mut group = c.benchmark_group("negative_inputs");
+    group.bench_function("hash_small_input", |b| {
+        b.iter(|| generator.hash(black_box(&small_x)))
+    });
+    group.bench_function("hash_big_input", |b| {
+        b.iter(|| generator.hash(black_box(&big_x)))
+    });
+    group.bench_function("hash_neg_big_input", |b| {
+        b.iter(|| generator.hash_neg(black_box(&positive_big_x)))
+    });
+    group.finish();
+}
+
+fn reduced_inputs(c: &mut Criterion) {
+    let generator = HRPPHICT::new(THRESHOLD, LAMBDA);
+    let x = (BigInt::from(1) << 256u32) + 12345;
+
+    let mut group = c.benchmark_group("reduced_inputs");
+    group.bench_function("hash", |b| b.iter(|| generator.hash(black_box(&x))));
+    group.bench_function("hash_reduced", |b| {
+        b.iter(|| generator.hash_reduced(black_box(&x), true))
+    });
+    group.finish();
+}
+
+fn compact_sums(c: &mut Criterion) {
+    let generator = HRPPHICT::new(THRESHOLD, LAMBDA);
+    let compact: Vec<_> = (0..1000)
+        .map(|x| generator.hash_small(x).compact())
+        .collect();
+
+    let mut group = c.benchmark_group("compact_sums");
+    group.bench_function("fold", |b| {
+        b.iter(|| {
+            black_box(&compact)
+                .iter()
+                .map(|h| generator.expand(h.clone()))
+                .fold(generator.zero_hash(), |acc, h| acc + h)
+        })
+    });
+    group.bench_function("sum_compact", |b| {
+        b.iter(|| generator.sum_compact(black_box(&compact)))
+    });
+    group.finish();
+}
+
+fn array_inputs(c: &mut Criterion) {
+    let generator = HRPPHICT::new(THRESHOLD, LAMBDA);
+    let xs: [BigInt; 16] = std::array::from_fn(|i| (BigInt::from(1) << 512u32) * i - 7);
+
+    let mut group = c.benchmark_group("array_inputs");
+    group.bench_function("hash_each", |b| {
+        b.iter(|| {
+            black_box(&xs)
+                .iter()
+                .map(|x| generator.hash(x))
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("hash_array", |b| {
+        b.iter(|| generator.hash_array(black_box(&xs)))
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    small_inputs,
+    negative_inputs,
+    reduced_inputs,
+    compact_sums,
+    array_inputs
+);
+criterion_main!(benches);
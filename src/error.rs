@@ -0,0 +1,45 @@
+use crate::hashes::{CrtError, DecodeError, HashArithError, ParamError, ValidationError};
+use thiserror::Error;
+
+/**
+Error returned by every fallible public method of the crate.
+
+The variants wrap the detailed errors of each operation, so callers can
+match on one type and still inspect the cause.
+*/
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The parameters of a generator break a constraint
+    #[error(transparent)]
+    ParamInvalid(#[from] ParamError),
+    /// Hashes combined together do not fit each other or the generator
+    #[error(transparent)]
+    ArithmeticMismatch(#[from] HashArithError),
+    /// Hashes cannot be combined by the Chinese remainder theorem
+    #[error(transparent)]
+    Crt(#[from] CrtError),
+    /// Bytes do not decode to a hash or parameters
+    #[error(transparent)]
+    Decode(#[from] DecodeError),
+    /// A hash breaks an invariant or does not belong to the generator
+    #[error(transparent)]
+    InvalidHash(#[from] ValidationError),
+    /// A group element shares a factor with n and has no inverse
+    #[error("group element is not invertible modulo n")]
+    InverseFailed,
+    /// No candidate in [-t,t] matches the hash
+    #[error("no candidate matches the hash")]
+    OutOfRange,
+    /// Several candidates match the hash, listed in scan order
+    #[error("{} candidates match the hash", .0.len())]
+    Ambiguous(Vec<i32>),
+    /// A value does not open the hash
+    #[error("value does not open the hash")]
+    InvalidOpening,
+    /// A timestamp lies before the Unix epoch
+    #[error("timestamp is before the Unix epoch")]
+    BeforeEpoch,
+    /// Key generation kept producing moduli of another bit length
+    #[error("no modulus of exactly {0} bits was generated")]
+    BitLengthUnattainable(u64),
+}
@@ -1,150 +1,1891 @@
 extern crate modinverse;
 extern crate num_bigint;
 extern crate rsa;
-use num_bigint::{BigInt, BigUint, RandBigInt, ToBigInt};
+use encoding::put_biguint;
+use hmac::{Hmac, Mac};
+use num_bigint::{BigInt, BigUint, RandBigInt, Sign, ToBigInt};
+use num_integer::Integer;
 use num_traits::{One, ToPrimitive, Zero};
-use rsa::traits::PublicKeyParts;
+use rand::{CryptoRng, RngCore};
+use rsa::traits::{PrivateKeyParts, PublicKeyParts};
 use rsa::RsaPrivateKey;
 use rsa::RsaPublicKey;
-use std::ops::{Add, Sub};
+use secret::SecretUint;
+use sha2::{Digest, Sha256};
+use std::io::{self, Read};
+use std::ops::{Add, Div, Sub};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use subtle::{Choice, ConstantTimeEq};
+
+mod aux;
+mod cache;
+mod complex;
+mod encoding;
+mod group;
+#[cfg(feature = "json")]
+mod jwk;
+mod pair;
+mod params;
+mod primes;
+mod running;
+mod secret;
+mod split;
+mod vectors;
+mod verifier;
+mod wide;
+pub use aux::ExtendedHash;
+pub use cache::SmallValueCache;
+pub use complex::ComplexHash;
+pub use encoding::{DecodeError, HASH_ENCODING_VERSION};
+pub use group::GroupElement;
+#[cfg(feature = "json")]
+pub use jwk::JWK_KEY_TYPE;
+pub use pair::{PairGen, PairHash};
+pub use params::{ParamDescription, ParamError, ParamViolation, PublicParams};
+pub use running::RunningCommitment;
+pub use split::{PublicResidue, SecretGroupElement};
+pub use vectors::{TEST_VECTOR_LAMBDA, TEST_VECTOR_THRESHOLD};
+pub use verifier::VerifierContext;
+pub use wide::{HRPPHICTWide, WideHash};
+
+// Smallest modulus the RSA library generates reliably
+const RSA_MIN_LAMBDA: u64 = 64;
+
+/// Smallest lambda HRPPHICT::new_safe_prime accepts. Below it there are
+/// too few safe primes of lambda/2 bits to pick two distinct ones.
+pub const SAFE_PRIME_MIN_LAMBDA: u64 = 18;
+
+/// Key generations HRPPHICT::new_exact_bits tries before giving up.
+pub const EXACT_BITS_MAX_ATTEMPTS: usize = 32;
+
+/// Exclusive bound of the search of HRPPHICT::recover_exponent_mod_order.
+pub const BSGS_MAX_EXPONENT: u64 = 1 << 32;
+
+/// Largest threshold HRPPHICT::range_hashes enumerates.
+pub const RANGE_HASHES_MAX_THRESHOLD: u16 = 1000;
+
+/// RSA modulus bits for 128-bit security, following NIST SP 800-57 Part 1.
+pub const LAMBDA_128BIT_SECURITY: u64 = 3072;
+/// RSA modulus bits for 256-bit security, following NIST SP 800-57 Part 1.
+pub const LAMBDA_256BIT_SECURITY: u64 = 15360;
 
 /**
 Struct for Integer Close to HRPPH generator.
 
-t: threshold used for judging if the input of hash is within [-t,t]
-d: small modulus for enumerating potential items
-s: half of enumerating test cases, it is less than 100
-a: random number for randomizing the collision resistant hash value
-n: big modulus for collision resistant hash
-*/
-#[derive(Debug)]
-pub struct HRPPHICT {
-    t: u16,
-    d: u16,
-    s: u16,
-    a: BigUint,
-    n: BigUint,
-}
+t: threshold used for judging if the input of hash is within [-t,t]
+d: small modulus for enumerating potential items
+s: half of enumerating test cases, it is less than 100
+a: random number for randomizing the collision resistant hash value
+n: big modulus for collision resistant hash
+a_inv: inverse of a modulo n, cached for hashing negative inputs
+order: order of the subgroup a generates, known only for safe-prime moduli
+factors: the primes p and q with n = p*q, kept only when the caller knows them
+center: origin of the accepted range [center-t, center+t], zero unless built with new_centered
+aux_d: modulus of the informational residue of hash_extended, set by with_aux_residue
+
+a, a_inv, order and factors are secret-sensitive and are wiped on drop
+with the zeroize feature; n, t, d and s are public.
+*/
+#[derive(Debug, Clone)]
+pub struct HRPPHICT {
+    t: u16,
+    d: u16,
+    s: u16,
+    a: SecretUint,
+    n: BigUint,
+    a_inv: SecretUint,
+    order: Option<SecretUint>,
+    factors: Option<(SecretUint, SecretUint)>,
+    center: BigInt,
+    aux_d: Option<u16>,
+}
+
+/**
+Struct for hash value of Integer Close To HRPPH.
+
+r: small remains for enumerating potential items
+g: collision resistant hash value, an element of the group modulo n
+d: small modulus for enumerating potential items
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hash {
+    r: u16,
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    g: GroupElement,
+    d: u16,
+}
+
+/**
+Struct for a hash stripped of its parameters.
+
+d and n are the same for every hash of a generator, so they are dropped
+to save memory when many hashes are kept; HRPPHICT::expand restores them.
+
+r: small remains for enumerating potential items
+g: collision resistant hash value
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompactHash {
+    r: u16,
+    g: BigUint,
+}
+
+/**
+Struct for the operations a generator supports, from HRPPHICT::capabilities.
+
+can_hash: hash inputs into commitments, which needs a != 1
+can_eval: recover in-range preimages with eval, which needs a whose order
+exceeds 2t, so no two candidates share a group element
+knows_order: reduce exponents modulo the order of a, which needs the
+order or factors of n from which randomizer_order finds it
+can_rehash: move hashes to another generator with rehash_from_eval, which
+evaluates them under this one
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    pub can_hash: bool,
+    pub can_eval: bool,
+    pub knows_order: bool,
+    pub can_rehash: bool,
+}
+
+/**
+Struct bundling a hash with its claimed opening.
+
+hash: the transmitted hash
+opening: the value claimed to be its preimage
+*/
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Commitment {
+    pub hash: Hash,
+    pub opening: BigInt,
+}
+
+impl Commitment {
+    /**
+    Check that hashing the opening with gen reproduces the hash.
+    */
+    pub fn verify(&self, gen: &HRPPHICT) -> bool {
+        gen.hash(&self.opening) == self.hash
+    }
+}
+
+/**
+Struct for the opening of a hash, revealing its preimage.
+
+value: the preimage of the hash
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Opening {
+    pub value: BigInt,
+}
+
+/**
+Cause of Error::Crt, returned by Hash::crt_combine.
+*/
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CrtError {
+    /// The hashes disagree on r or d, so they cannot commit to the same value
+    #[error("hashes have different residues")]
+    ResidueMismatch,
+    /// The moduli share a factor, so CRT does not apply
+    #[error("moduli are not coprime")]
+    NotCoprime,
+}
+
+/**
+Cause of Error::ArithmeticMismatch, returned by batched hash arithmetic
+such as HRPPHICT::hash_diff_batch.
+*/
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum HashArithError {
+    /// The batches do not have the same number of hashes
+    #[error("batches have different lengths")]
+    LengthMismatch,
+    /// A hash was not produced under the parameters of this generator
+    #[error("hash parameters do not match")]
+    ParamMismatch,
+}
+
+/**
+Cause of Error::InvalidHash, returned by HRPPHICT::validate_hash.
+*/
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ValidationError {
+    /// The residue is not below d
+    #[error("residue {r} is not below d={d}")]
+    ResidueOutOfRange { r: u16, d: u16 },
+    /// The group element is zero or not reduced modulo n
+    #[error("group element must lie in [1, n)")]
+    ElementOutOfRange,
+    /// d or n differ from those of the generator
+    #[error("hash parameters do not match the generator")]
+    ParamMismatch,
+}
+
+impl HRPPHICT {
+    /**
+    Given a threshold and the bits of key, return a HRPPHICT generator.
+
+    Collision resistant hash is generated from RSA library.
+    The enumerating process is limited to no more than 200 rounds.
+
+    The RSA library refuses moduli below 64 bits. For such lambda, n is
+    instead the product of two random primes of lambda/2 bits, for a
+    low-security fast mode. INSECURE: such a modulus, and anything much
+    below LAMBDA_128BIT_SECURITY, is factored in no time and gives no
+    collision resistance. Only use it for tests and non-adversarial data.
+    */
+    pub fn new(threshold: u16, lambda: u64) -> HRPPHICT {
+        HRPPHICT::new_with_d(threshold, default_d(threshold), lambda)
+    }
+
+    // Same as new with the given d, falling back to a small modulus below the
+    // RSA minimum
+    fn new_with_d(threshold: u16, d: u16, lambda: u64) -> HRPPHICT {
+        if lambda < RSA_MIN_LAMBDA {
+            return HRPPHICT::new_small_modulus(threshold, d, lambda);
+        }
+        HRPPHICT::new_from_rng(threshold, d, lambda, &mut rand::thread_rng()).0
+    }
+
+    // Build an insecure generator whose modulus is too small for the RSA library
+    fn new_small_modulus(threshold: u16, d: u16, lambda: u64) -> HRPPHICT {
+        assert!(lambda >= 10, "lambda must be at least 10 bits");
+        let mut rng = rand::thread_rng();
+        let p = primes::gen_prime(&mut rng, lambda / 2);
+        let q = loop {
+            let q = primes::gen_prime(&mut rng, lambda - lambda / 2);
+            if q != p {
+                break q;
+            }
+        };
+        let mut generator =
+            HRPPHICT::from_primes(threshold, &p, &q).expect("generated factors are prime");
+        generator.d = d;
+        generator.s = threshold / d;
+        generator
+    }
+
+    /**
+    Same as new, but also return the RSA private key behind n.
+
+    This is meant for deployments that must escrow the factorization of n.
+    Anyone holding the private key knows the group order and can break the
+    collision resistance of the hash, so it must be stored at least as
+    carefully as any other signing key and never handed to hash producers.
+    The returned generator also keeps the primes, so randomizer_order works.
+    */
+    pub fn new_with_escrow(threshold: u16, lambda: u64) -> (HRPPHICT, RsaPrivateKey) {
+        let (mut generator, priv_key) = HRPPHICT::new_from_rng(
+            threshold,
+            default_d(threshold),
+            lambda,
+            &mut rand::thread_rng(),
+        );
+        let primes = priv_key.primes();
+        generator.factors = Some((
+            SecretUint::from_rsa(&primes[0]),
+            SecretUint::from_rsa(&primes[1]),
+        ));
+        (generator, priv_key)
+    }
+
+    // Build a generator with the given d and its RSA key from the randomness of rng
+    fn new_from_rng<R: CryptoRng + RngCore>(
+        threshold: u16,
+        d: u16,
+        lambda: u64,
+        rng: &mut R,
+    ) -> (HRPPHICT, RsaPrivateKey) {
+        let priv_key = gen_key(rng, lambda);
+        let (a, module) = gen_randomizer(rng, &priv_key, lambda);
+        let a_inv = modinverse(&a, &module).expect("randomizer is not invertible");
+
+        let generator = HRPPHICT {
+            t: threshold,
+            d,
+            s: threshold / d,
+            a: SecretUint::from(a),
+            n: module,
+            a_inv: SecretUint::from(a_inv),
+            order: None,
+            factors: None,
+            center: BigInt::zero(),
+            aux_d: None,
+        };
+        (generator, priv_key)
+    }
+
+    /**
+    Return count independent generators sharing threshold and lambda.
+
+    With the parallel feature the RSA keys are generated concurrently with
+    rayon, otherwise one after another. Every generator draws its own key
+    and randomizer.
+    */
+    pub fn new_many(count: usize, threshold: u16, lambda: u64) -> Vec<HRPPHICT> {
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            (0..count)
+                .into_par_iter()
+                .map(|_| HRPPHICT::new(threshold, lambda))
+                .collect()
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            (0..count)
+                .map(|_| HRPPHICT::new(threshold, lambda))
+                .collect()
+        }
+    }
+
+    /**
+    Recommend d for threshold so that eval scans at most max_rounds candidates.
+
+    eval tries up to ceil((2t+1)/d) candidates, about 2*(t/d)+1, while r
+    leaks log2(d) bits of every preimage: a larger d makes eval faster but
+    hides less. This returns the smallest d whose worst case fits in
+    max_rounds, the one leaking least within the budget. If no d fits,
+    which happens for max_rounds below 2, or below 3 when t is at least 2,
+    the largest valid d, threshold itself, is returned.
+    */
+    pub fn recommend_d(threshold: u16, max_rounds: usize) -> u16 {
+        assert!(threshold >= 1, "threshold must be at least 1");
+        let width = 2 * usize::from(threshold) + 1;
+        (1..=threshold)
+            .find(|&d| width.div_ceil(usize::from(d)) <= max_rounds)
+            .unwrap_or(threshold)
+    }
+
+    /**
+    Same as new, but pick d so that eval scans at most max_rounds candidates.
+
+    d comes from recommend_d, so eval cost is bounded first and the residue
+    leaks the least the budget allows. lambda below 64 falls back to the
+    insecure small modulus, as for new.
+    */
+    pub fn new_with_rounds(threshold: u16, lambda: u64, max_rounds: usize) -> HRPPHICT {
+        HRPPHICT::new_with_d(
+            threshold,
+            HRPPHICT::recommend_d(threshold, max_rounds),
+            lambda,
+        )
+    }
+
+    /**
+    Same as new, but with d the largest prime not above the d new would pick.
+
+    With d prime the residues form the field Z/dZ, so every nonzero residue
+    is invertible: scaling by any k not divisible by d can be undone on r,
+    and linear relations between residues can be solved. d is at least 2,
+    so the residue leaks at most as much as for new, or one bit for
+    thresholds where new would take d = 1. lambda below 64 falls back to
+    the insecure small modulus, as for new. Panics if threshold is below 2.
+    */
+    pub fn new_prime_d(threshold: u16, lambda: u64) -> HRPPHICT {
+        assert!(threshold >= 2, "threshold must be at least 2");
+        let d = (2..=default_d(threshold).max(2))
+            .rev()
+            .find(|&d| primes::is_small_prime(d))
+            .unwrap();
+        HRPPHICT::new_with_d(threshold, d, lambda)
+    }
+
+    /**
+    Same as new, but regenerate until n has exactly exact_bits bits.
+
+    Key generation may return a modulus one bit short, e.g. two primes of
+    lambda/2 bits in the small-modulus mode of new. After
+    EXACT_BITS_MAX_ATTEMPTS tries without the exact length this fails with
+    Error::BitLengthUnattainable.
+    */
+    pub fn new_exact_bits(threshold: u16, exact_bits: u64) -> Result<HRPPHICT, crate::Error> {
+        (0..EXACT_BITS_MAX_ATTEMPTS)
+            .map(|_| HRPPHICT::new(threshold, exact_bits))
+            .find(|generator| generator.n.bits() == exact_bits)
+            .ok_or(crate::Error::BitLengthUnattainable(exact_bits))
+    }
+
+    /**
+    Return a generator whose modulus gives 128-bit security.
+
+    lambda is LAMBDA_128BIT_SECURITY (3072 bits), the modulus size NIST
+    SP 800-57 pairs with 128-bit symmetric strength.
+    */
+    pub fn preset_128bit_security(threshold: u16) -> HRPPHICT {
+        HRPPHICT::new(threshold, LAMBDA_128BIT_SECURITY)
+    }
+
+    /**
+    Return a generator whose modulus gives 256-bit security.
+
+    lambda is LAMBDA_256BIT_SECURITY (15360 bits), the modulus size NIST
+    SP 800-57 pairs with 256-bit symmetric strength. Key generation at this
+    size takes a long time.
+    */
+    pub fn preset_256bit_security(threshold: u16) -> HRPPHICT {
+        HRPPHICT::new(threshold, LAMBDA_256BIT_SECURITY)
+    }
+
+    /**
+    Return a generator whose modulus is a product of two safe primes.
+
+    n = p*q with p = 2p'+1 and q = 2q'+1, and a is a random square, so it
+    lies in the subgroup of quadratic residues of order p'q'. The generator
+    keeps that order to let is_valid_element detect group elements outside
+    the subgroup. Knowing the order breaks collision resistance just like
+    knowing the factors of n, so such a generator must stay with a trusted
+    verifier; the exported params do not carry it. Safe primes are rare,
+    so key generation is much slower than for new. Panics if lambda is below
+    SAFE_PRIME_MIN_LAMBDA.
+    */
+    pub fn new_safe_prime(threshold: u16, lambda: u64) -> HRPPHICT {
+        assert!(
+            lambda >= SAFE_PRIME_MIN_LAMBDA,
+            "lambda must be at least {} bits for safe primes",
+            SAFE_PRIME_MIN_LAMBDA
+        );
+        let mut rng = rand::thread_rng();
+        let d = default_d(threshold);
+
+        let (p, p_half) = primes::gen_safe_prime(&mut rng, lambda / 2);
+        let (q, q_half) = loop {
+            let (q, q_half) = primes::gen_safe_prime(&mut rng, lambda - lambda / 2);
+            if q != p {
+                break (q, q_half);
+            }
+        };
+        let n = &p * &q;
+        let a = loop {
+            let root = rng.gen_biguint_below(&n);
+            let a = root.modpow(&BigUint::from(2u32), &n);
+            if !a.is_one() && GroupElement::new(a.clone(), n.clone()).is_invertible() {
+                break a;
+            }
+        };
+        let a_inv = modinverse(&a, &n).expect("randomizer is not invertible");
+
+        HRPPHICT {
+            t: threshold,
+            d,
+            s: threshold / d,
+            a: SecretUint::from(a),
+            n,
+            a_inv: SecretUint::from(a_inv),
+            order: Some(SecretUint::from(p_half * q_half)),
+            factors: Some((SecretUint::new(&p), SecretUint::new(&q))),
+            center: BigInt::zero(),
+            aux_d: None,
+        }
+    }
+
+    /**
+    Same as new, but accept the range [center-t, center+t] instead of [-t,t].
+
+    hash shifts every input by -center and eval shifts the recovered value
+    back, so a hash opens to its own preimage as long as it lies within t
+    of the center. The offset cancels in differences: hash(x) - hash(y) is
+    the uncentered hash of x - y, which diff_eval opens. Sums do not cancel
+    it, hash(x) + hash(y) commits to x + y - 2*center, so aggregate with
+    differences or add_value. eval returns None when center + the scanned
+    value does not fit in i32; eval_bigint does not have that limit. The
+    center is not part of the exported params, so it must be passed to
+    verifiers separately.
+    */
+    pub fn new_centered(center: BigInt, threshold: u16, lambda: u64) -> HRPPHICT {
+        let mut generator = HRPPHICT::new(threshold, lambda);
+        generator.center = center;
+        generator
+    }
+
+    /**
+    Hash an integer x.
+
+    Every BigInt is a valid input, including values far beyond i128 and
+    their negatives: x is used as the exponent without truncation, negative
+    inputs are inverted modulo n, and r = x mod d always lies in [0, d).
+    r is the least non-negative residue: it is computed from |x| mod d,
+    which fits in u16, and folded to (d - |x| mod d) mod d for negative x,
+    so it never goes through a negative remainder. A centered generator
+    hashes x - center instead.
+    */
+    pub fn hash(&self, x: &BigInt) -> Hash {
+        self.hash_reduced(x, false).0
+    }
+
+    /**
+    Hash x, skipping the sign handling when the caller vouches for it.
+
+    hash never reduces the exponent, since the group order is unknown; the
+    only reduction it performs is folding a negative x into canonical form,
+    by using the inverse base and shifting r = x mod d into [0, d). With
+    already_reduced the caller guarantees x >= 0, e.g. because x was reduced
+    modulo some public value, and that step is skipped. The returned flag
+    tells whether the fold happened. Passing already_reduced with a negative
+    x yields a wrong hash. A centered generator hashes x - center, whose
+    sign x does not tell, so there already_reduced is ignored and the fold
+    happens whenever x is below the center.
+    */
+    pub fn hash_reduced(&self, x: &BigInt, already_reduced: bool) -> (Hash, bool) {
+        debug_assert!(
+            !already_reduced || !self.center.is_zero() || x.sign() != Sign::Minus,
+            "input reported as reduced is negative"
+        );
+        let (g, r, negative) = self.hash_parts(x, already_reduced);
+        (self.hash_from_parts(g, r), negative)
+    }
+
+    // Hash the exponent x itself, ignoring the center
+    fn hash_uncentered(&self, x: &BigInt, already_reduced: bool) -> (Hash, bool) {
+        let (g, r, negative) = self.exponent_parts(x, already_reduced);
+        (self.hash_from_parts(g, r), negative)
+    }
+
+    // g, r and the fold flag of hash_reduced, without building a Hash
+    fn hash_parts(&self, x: &BigInt, already_reduced: bool) -> (BigUint, u16, bool) {
+        if self.center.is_zero() {
+            self.exponent_parts(x, already_reduced)
+        } else {
+            self.exponent_parts(&(x - &self.center), false)
+        }
+    }
+
+    // g = a^x mod n, r = x mod d and the fold flag for the exponent x itself
+    fn exponent_parts(&self, x: &BigInt, already_reduced: bool) -> (BigUint, u16, bool) {
+        let negative = !already_reduced && x.sign() == Sign::Minus;
+        let magnitude = x.magnitude();
+        let g = if negative {
+            self.a_inv.value().modpow(magnitude, &(self.n))
+        } else {
+            self.a.value().modpow(magnitude, &(self.n))
+        };
+        let r = fold_residue(magnitude, negative, self.d.into()) as u16;
+        (g, r, negative)
+    }
+
+    // Wrap g and r as a hash of this generator
+    fn hash_from_parts(&self, g: BigUint, r: u16) -> Hash {
+        let h = Hash {
+            g: self.element(g),
+            r,
+            d: self.d,
+        };
+        h.debug_assert_canonical();
+        h
+    }
+
+    /**
+    Hash -x by hashing x on the positive branch and negating the hash.
+
+    The result equals hash(-x). For x >= 0 the hash of x is inverted; a
+    negative x already has a non-negative -x, which is hashed directly. A
+    centered generator hashes -x directly, since negating would also negate
+    the center. It is not faster than hash(-x): the negative branch raises
+    the cached inverse of a at the cost of the positive one, so the extra
+    inversion makes hash_neg about 20% slower in the negative_inputs
+    benchmark with a 2048-bit n.
+    */
+    pub fn hash_neg(&self, x: &BigInt) -> Hash {
+        if x.sign() == Sign::Minus || !self.center.is_zero() {
+            return self.hash(&-x);
+        }
+        self.hash_uncentered(x, true).0.inverse()
+    }
+
+    /**
+    Hash a small input given as i32.
+
+    This is meant for inputs known to lie in [-t,t]: the exponent fits in a
+    u32 and no BigInt is built. The result is the same as hash(&BigInt::from(x)).
+    */
+    pub fn hash_small(&self, x: i32) -> Hash {
+        if !self.center.is_zero() {
+            return self.hash(&BigInt::from(x));
+        }
+        let h = Hash {
+            r: x.rem_euclid(self.d.into()) as u16,
+            g: self.element(self.pow_small(x.into())),
+            d: self.d,
+        };
+        h.debug_assert_canonical();
+        h
+    }
+
+    /**
+    Iterate over (x, hash(x)) for every x in [-t,t], in ascending order.
+
+    For a centered generator x runs over [center-t, center+t] instead,
+    leaving out values that do not fit in i32. This is meant for exhaustive
+    tests of eval, so it panics when t is above RANGE_HASHES_MAX_THRESHOLD:
+    each item costs a modpow.
+    */
+    pub fn range_hashes(&self) -> impl Iterator<Item = (i32, Hash)> + '_ {
+        assert!(
+            self.t <= RANGE_HASHES_MAX_THRESHOLD,
+            "t={} is above {} for range_hashes",
+            self.t,
+            RANGE_HASHES_MAX_THRESHOLD
+        );
+        let top: i32 = self.t.into();
+        (-top..=top)
+            .filter_map(move |c| self.centered(c))
+            .map(move |x| (x, self.hash_small(x)))
+    }
+
+    /**
+    Hash a timestamp as its whole seconds since the Unix epoch.
+
+    Sub-second precision is dropped. Timestamps before the epoch are
+    rejected with Error::BeforeEpoch.
+    */
+    pub fn hash_timestamp(&self, ts: SystemTime) -> Result<Hash, crate::Error> {
+        let since_epoch = ts
+            .duration_since(UNIX_EPOCH)
+            .map_err(|_| crate::Error::BeforeEpoch)?;
+        Ok(self.hash_duration(since_epoch))
+    }
+
+    /**
+    Hash a duration as its whole seconds, dropping sub-second precision.
+    */
+    pub fn hash_duration(&self, d: Duration) -> Hash {
+        self.hash(&BigInt::from(d.as_secs()))
+    }
+
+    /**
+    Hash x clamped into the accepted range, so eval always succeeds.
+
+    Inputs below -t hash as -t and inputs above t as t; for a centered
+    generator the range is [center-t, center+t]. This loses information:
+    every out-of-range input opens to the nearest bound, not to itself.
+    */
+    pub fn hash_clamped(&self, x: &BigInt) -> Hash {
+        let t = BigInt::from(self.t);
+        let low = &self.center - &t;
+        let high = &self.center + &t;
+        self.hash(&x.clone().clamp(low, high))
+    }
+
+    /**
+    Hash x after reducing it into the balanced range of modulus.
+
+    The representative lies in center + (-modulus/2, modulus/2], so for an
+    odd modulus 2k+1 it is within k of the center, which is zero unless set
+    with new_centered. This only makes eval recover the representative when
+    modulus <= 2t+1; for a larger modulus it may fall outside the range.
+    Panics if modulus is not positive.
+    */
+    pub fn hash_mod(&self, x: &BigInt, modulus: &BigInt) -> Hash {
+        assert!(modulus.sign() == Sign::Plus, "modulus must be positive");
+        let mut y = (x - &self.center).mod_floor(modulus);
+        if &y * 2 > *modulus {
+            y -= modulus;
+        }
+        self.hash_uncentered(&y, false).0
+    }
+
+    /**
+    Hash the integer encoded by big-endian bytes.
+
+    With signed the bytes are read as two's complement, otherwise as an
+    unsigned magnitude. Empty input is zero.
+    */
+    pub fn hash_bytes(&self, bytes: &[u8], signed: bool) -> Hash {
+        let x = if signed {
+            BigInt::from_signed_bytes_be(bytes)
+        } else {
+            BigInt::from_bytes_be(Sign::Plus, bytes)
+        };
+        self.hash(&x)
+    }
+
+    /**
+    Hash a bit vector packed into a non-negative integer, LSB first.
+
+    bits[i] is the coefficient of 2^i, so [true, false, true] hashes as 5.
+    Trailing false bits do not change the value.
+    */
+    pub fn hash_bits(&self, bits: &[bool]) -> Hash {
+        let mut x = BigUint::zero();
+        for (i, bit) in bits.iter().enumerate() {
+            if *bit {
+                x.set_bit(i as u64, true);
+            }
+        }
+        self.hash(&BigInt::from(x))
+    }
+
+    /**
+    Same as hash_bytes, but read the bytes from reader until its end.
+    */
+    pub fn hash_reader<R: Read>(&self, mut reader: R, signed: bool) -> io::Result<Hash> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        Ok(self.hash_bytes(&bytes, signed))
+    }
+
+    /**
+    Build a hash from an exponent and a residue that need not agree.
+
+    g is a^exp mod n and r is residue as given, so exp mod d may differ
+    from residue. This is a testing utility for building deliberately
+    inconsistent hashes to exercise error paths: eval on such a hash scans
+    the candidates congruent to residue, none of which is exp, and returns
+    (None, false) unless a collision occurs. Panics if residue is not below d.
+    */
+    pub fn hash_from_exponent(&self, exp: &BigInt, residue: u16) -> Hash {
+        assert!(
+            residue < self.d,
+            "residue {} is not below d={}",
+            residue,
+            self.d
+        );
+        let h = Hash {
+            r: residue,
+            g: self.element(self.pow_a(exp)),
+            d: self.d,
+        };
+        h.debug_assert_canonical();
+        h
+    }
+
+    /**
+    Hash an inner hash with this generator, for two-level commitments.
+
+    The encoding of inner.to_bytes is read as a big-endian unsigned integer
+    and hashed. The version byte leads the encoding, so the integer keeps
+    every byte and decodes back to inner. The outer hash covers the bytes
+    of inner, not its preimage: it is not homomorphic in the inner value,
+    and adding outer hashes does not correspond to adding inner ones.
+    */
+    pub fn hash_commitment(&self, inner: &Hash) -> Hash {
+        self.hash(&BigInt::from_bytes_be(Sign::Plus, &inner.to_bytes()))
+    }
+
+    /**
+    Hash every input of a slice, keeping their order.
+
+    With the parallel feature the inputs are hashed concurrently with
+    rayon, otherwise one after another.
+    */
+    pub fn hash_batch(&self, xs: &[BigInt]) -> Vec<Hash> {
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            xs.par_iter().map(|x| self.hash(x)).collect()
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            xs.iter().map(|x| self.hash(x)).collect()
+        }
+    }
+
+    /**
+    Hash inputs in chunks of chunk_size, handing every chunk to sink.
+
+    Each chunk is hashed with hash_batch, so in parallel with the parallel
+    feature, and passed to sink before the next is read. At most one chunk
+    of inputs and hashes is held at a time, so peak memory is bounded by
+    chunk_size whatever the length of inputs. The last chunk may be
+    shorter; an empty iterator never calls sink. Panics if chunk_size is 0.
+    */
+    pub fn hash_chunked<I: Iterator<Item = BigInt>>(
+        &self,
+        mut inputs: I,
+        chunk_size: usize,
+        mut sink: impl FnMut(Vec<Hash>),
+    ) {
+        assert!(chunk_size > 0, "chunk_size must be positive");
+        loop {
+            let chunk: Vec<BigInt> = inputs.by_ref().take(chunk_size).collect();
+            if chunk.is_empty() {
+                break;
+            }
+            sink(self.hash_batch(&chunk));
+        }
+    }
+
+    /**
+    Hash every element of a fixed-size array.
+
+    This is a thin wrapper calling hash on each element, so it is no faster
+    than a loop. num-bigint sets up its Montgomery context inside every
+    modpow and does not expose it, so no reduction work can be shared
+    between the elements; the array form only saves callers the collection
+    into a Vec, and the array_inputs benchmark tracks that it stays on par.
+    */
+    pub fn hash_array<const N: usize>(&self, xs: &[BigInt; N]) -> [Hash; N] {
+        std::array::from_fn(|i| self.hash(&xs[i]))
+    }
+
+    /**
+    Hash the sum of all values in xs.
+
+    The preimages are summed first and hashed once, so the result equals
+    folding the individual hashes with `+` while paying for a single modpow.
+    On a centered generator each of the k hashes commits to x - center, so
+    the result commits to sum(xs) - k * center as the fold does. An empty
+    slice gives zero_hash, which is the hash of zero when uncentered.
+    */
+    pub fn hash_sum(&self, xs: &[BigInt]) -> Hash {
+        let sum: BigInt = xs.iter().sum();
+        let offset = sum - &self.center * BigInt::from(xs.len());
+        self.hash_uncentered(&offset, false).0
+    }
+
+    /**
+    Hash the evaluation p(z) of the polynomial p(z) = sum(coeffs[i] * z^i).
+
+    The evaluation uses Horner's method over BigInt, which cannot overflow,
+    so the result is exactly hash(&p(z)). No coefficients means p = 0.
+    */
+    pub fn hash_poly_eval(&self, coeffs: &[BigInt], z: &BigInt) -> Hash {
+        let value = coeffs
+            .iter()
+            .rev()
+            .fold(BigInt::zero(), |acc, c| acc * z + c);
+        self.hash(&value)
+    }
+
+    /**
+    Check that hash(a + b) == hash(a) + hash(b) holds for this pair.
+
+    This is the homomorphism every other combination relies on; a false
+    result means the residue or the group arithmetic is broken for these
+    inputs. The check runs on the uncentered hash, so a center set with
+    new_centered does not affect it.
+    */
+    pub fn check_additive(&self, a: &BigInt, b: &BigInt) -> bool {
+        let hash = |x: &BigInt| self.hash_uncentered(x, false).0;
+        hash(&(a + b)) == hash(a) + hash(b)
+    }
+
+    /**
+    Check that hash(a - b) == hash(a) - hash(b) holds for this pair.
+
+    This exercises the inverse of a hash, including the residue of a b
+    with b mod d == 0, which must negate to 0 rather than d. As for
+    check_additive the center is ignored.
+    */
+    pub fn check_subtractive(&self, a: &BigInt, b: &BigInt) -> bool {
+        let hash = |x: &BigInt| self.hash_uncentered(x, false).0;
+        hash(&(a - b)) == hash(a) - hash(b)
+    }
+
+    /**
+    Add the hash of the plain integer x to h.
+
+    This equals h + self.hash(x) for an uncentered generator; a centered
+    one adds x itself, not x - center, so the offset is not counted twice.
+    It lets accumulations mix terms that are already hashed with plain
+    integers without hashing them by hand. A `Hash + BigInt` operator is
+    not offered because hashing x needs the generator.
+    */
+    pub fn add_value(&self, h: Hash, x: &BigInt) -> Hash {
+        h + self.hash_uncentered(x, false).0
+    }
+
+    /**
+    Return the hash of zero, the identity for hash addition.
+    */
+    pub fn zero_hash(&self) -> Hash {
+        Hash {
+            r: 0,
+            g: GroupElement::one(self.n.clone()),
+            d: self.d,
+        }
+    }
+
+    pub fn eval(&self, h: &Hash) -> (Option<i32>, bool) {
+        self.recenter(self.scan(h))
+    }
+
+    /**
+    Same as eval(&hash(x)), without building the intermediate hash.
+
+    r and g are computed by the same helper as hash and scanned directly,
+    so no Hash and no copy of n is allocated, which helps in tight loops
+    that only need the eval result.
+    */
+    pub fn hash_then_eval(&self, x: &BigInt) -> (Option<i32>, bool) {
+        let (g, r, _) = self.hash_parts(x, false);
+        self.recenter(self.scan_parts(r, &g))
+    }
+
+    /**
+    Same as eval, but check the guess hint before scanning.
+
+    When hint is in range, congruent to r and matches g, (Some(hint), true)
+    is returned after a single modpow; otherwise the full scan runs. With
+    broken parameters where several candidates match, a matching hint wins
+    over the first candidate eval would return.
+    */
+    pub fn eval_with_hint(&self, h: &Hash, hint: i32) -> (Option<i32>, bool) {
+        let shifted = (BigInt::from(hint) - &self.center).to_i64();
+        if let Some(c) = shifted {
+            let in_range = c.abs() <= i64::from(self.t);
+            if in_range
+                && c.rem_euclid(self.d.into()) == i64::from(h.r)
+                && self.eqcheck(c as i32, &h.g.value)
+            {
+                return (Some(hint), true);
+            }
+        }
+        self.eval(h)
+    }
+
+    // Shift a scanned value back by the center into the result of eval
+    fn recenter(&self, scanned: Option<i32>) -> (Option<i32>, bool) {
+        match scanned.and_then(|c| self.centered(c)) {
+            Some(x) => (Some(x), true),
+            None => (None, false),
+        }
+    }
+
+    // Add the center to a scanned offset, or None if the sum leaves i32
+    fn centered(&self, c: i32) -> Option<i32> {
+        if self.center.is_zero() {
+            Some(c)
+        } else {
+            (&self.center + c).to_i32()
+        }
+    }
+
+    /**
+    Derive a deterministic blinding scalar from counter.
+
+    The nonce is HMAC-SHA256 keyed with a, written as a u32 length followed
+    by its big-endian bytes, over the domain tag "hrpph_ict nonce" followed
+    by counter as a big-endian u64, read as a non-negative 256-bit integer.
+    Parties holding the same generator derive the same nonce for a counter
+    without it being sent, including generators rebuilt with from_params.
+    The nonce is only as secret as a: anyone given the params can derive it,
+    so it blinds towards verifiers but not towards other hash producers.
+    */
+    pub fn nonce_from_counter(&self, counter: u64) -> BigInt {
+        let mut key = Vec::new();
+        put_biguint(&mut key, self.a.value());
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&key).expect("HMAC accepts keys of any length");
+        secret::wipe(&mut key);
+        mac.update(b"hrpph_ict nonce");
+        mac.update(&counter.to_be_bytes());
+        BigInt::from_bytes_be(Sign::Plus, &mac.finalize().into_bytes())
+    }
+
+    /**
+    Hash x bound to the context salt.
+
+    The salt is mapped to the offset k = SHA-256(salt), read as a big-endian
+    unsigned integer, and the result is hash(x + k) = a^(x+k) mod n with
+    residue (x+k) mod d. Hashes under different salts differ, so a hash
+    cannot be replayed in another context without knowing both salts.
+    Salted hashes still add with unsalted ones: hash_salted(x, salt) +
+    hash(y) equals hash_salted(x + y, salt). This only holds uncentered: on
+    a centered generator both terms carry -center, so the sum equals
+    hash_salted(x + y - center, salt). eval_salted removes k again.
+    */
+    pub fn hash_salted(&self, x: &BigInt, salt: &[u8]) -> Hash {
+        self.hash(&(x + salt_offset(salt)))
+    }
+
+    /**
+    Same as eval, but for a hash produced by hash_salted under salt.
+
+    The hash of the salt offset is subtracted before the scan, so the
+    result is the unsalted preimage. A hash made under another salt does
+    not open, except by the chance of a collision.
+    */
+    pub fn eval_salted(&self, h: &Hash, salt: &[u8]) -> (Option<i32>, bool) {
+        self.eval(&(h.clone() - self.hash_uncentered(&salt_offset(salt), true).0))
+    }
+
+    /**
+    Recover the difference of the preimages of a and b.
+
+    This is a - b followed by eval, the core of closeness testing: it
+    returns (Some(x_a - x_b), true) when the preimages are within t of each
+    other. The centers of a and b cancel, so none is added back. Hashes
+    from another generator would make the subtraction panic; they are
+    rejected with (None, false) instead.
+    */
+    pub fn diff_eval(&self, a: &Hash, b: &Hash) -> (Option<i32>, bool) {
+        if !self.accepts(a) || !self.accepts(b) {
+            return (None, false);
+        }
+        match self.scan(&(a.clone() - b.clone())) {
+            Some(c) => (Some(c), true),
+            None => (None, false),
+        }
+    }
+
+    /**
+    Check that the preimage of hx is at most that of hy, within t.
+
+    This opens hy - hx with diff_eval and accepts iff the difference lies
+    in [0, t], so x > y and y - x > t are both rejected. The difference is
+    opened by the caller, who learns y - x exactly; anyone seeing the two
+    hashes learns (y - x) mod d from the residues.
+    */
+    pub fn prove_leq(&self, hx: &Hash, hy: &Hash) -> bool {
+        matches!(self.diff_eval(hy, hx), (Some(diff), true) if diff >= 0)
+    }
+
+    /**
+    Same as eval, but return the recovered value as a BigInt.
+
+    This keeps callers independent of the width of the threshold type.
+    */
+    pub fn eval_bigint(&self, h: &Hash) -> (Option<BigInt>, bool) {
+        match self.scan(h) {
+            Some(c) => (Some(&self.center + c), true),
+            None => (None, false),
+        }
+    }
+
+    /**
+    Move h from the old generator to this one.
+
+    The preimage is recovered with old.eval and hashed again under self,
+    which is how hashes migrate when a is rotated. Only values in the range
+    of old can be recovered; for any other hash this returns None.
+    */
+    pub fn rehash_from_eval(&self, old: &HRPPHICT, h: &Hash) -> Option<Hash> {
+        old.eval_bigint(h).0.map(|x| self.hash(&x))
+    }
+
+    /**
+    Restore the parameters of a compact hash of this generator.
+    */
+    pub fn expand(&self, c: CompactHash) -> Hash {
+        let h = Hash {
+            r: c.r,
+            g: self.element(c.g),
+            d: self.d,
+        };
+        h.debug_assert_canonical();
+        h
+    }
+
+    /**
+    Add up compact hashes of this generator.
+
+    The residues are summed once and reduced modulo d at the end, and the
+    group elements are multiplied without building intermediate hashes.
+    The product is still reduced modulo n after every multiplication:
+    deferring reductions, in batches or with a product tree, makes the
+    multiplications of num-bigint slower than the reductions it saves.
+    The result equals folding the expanded hashes with `+`; no hashes give
+    the hash of zero.
+    */
+    pub fn sum_compact(&self, hs: &[CompactHash]) -> CompactHash {
+        let r = hs.iter().map(|h| u64::from(h.r)).sum::<u64>() % u64::from(self.d);
+        let g = hs
+            .iter()
+            .fold(BigUint::one(), |acc, h| (acc * &h.g) % &self.n);
+        CompactHash { r: r as u16, g }
+    }
+
+    /**
+    Return the hashes of the pairwise differences a[i] - b[i].
+
+    Running eval on each result tells whether the corresponding preimages
+    are within t of each other. Fails if the batches have different
+    lengths, if any hash was not produced by this generator, or with
+    Error::InverseFailed if some b[i] cannot be inverted.
+    */
+    pub fn hash_diff_batch(&self, a: &[Hash], b: &[Hash]) -> Result<Vec<Hash>, crate::Error> {
+        if a.len() != b.len() {
+            return Err(HashArithError::LengthMismatch.into());
+        }
+        if !a.iter().chain(b).all(|h| self.accepts(h)) {
+            return Err(HashArithError::ParamMismatch.into());
+        }
+        a.iter()
+            .zip(b)
+            .map(|(x, y)| Ok(x.clone() + y.checked_inverse()?))
+            .collect()
+    }
+
+    /**
+    Return the hashes of the consecutive differences hs[i] - hs[i-1].
+
+    The result has one hash less than hs, and eval on its i-th entry tells
+    whether the series moved by at most t between steps i and i+1. Fails if
+    any hash was not produced by this generator, or with
+    Error::InverseFailed if some hash cannot be inverted.
+    */
+    pub fn hash_deltas(&self, hs: &[Hash]) -> Result<Vec<Hash>, crate::Error> {
+        if !hs.iter().all(|h| self.accepts(h)) {
+            return Err(HashArithError::ParamMismatch.into());
+        }
+        hs.windows(2)
+            .map(|w| Ok(w[1].clone() + w[0].checked_inverse()?))
+            .collect()
+    }
+
+    /**
+    Return the hash of the mean of the preimages of hs.
+
+    The hashes are added and the result is divided by k = hs.len() in the
+    exponent: g is raised to k^-1 modulo the order of a and r is multiplied
+    by k^-1 modulo d. This only works for generators whose order is known,
+    those from new_safe_prime, from_primes or new_with_escrow, and the
+    result is the hash of the mean only when k divides the sum. When the
+    order cannot be found from the factors, as for the random primes of
+    new_with_escrow, k^-1 is taken modulo the Carmichael value lcm(p-1, q-1)
+    instead, a multiple of the order. None is returned when the order is
+    unknown, hs is empty or foreign, or k is not invertible modulo the order
+    (or Carmichael value) or d.
+    */
+    pub fn hash_mean(&self, hs: &[Hash]) -> Option<Hash> {
+        if hs.is_empty() || !hs.iter().all(|h| self.accepts(h)) {
+            return None;
+        }
+        let order = self.order_bound()?;
+        let k = BigUint::from(hs.len());
+        let k_inv = SecretUint::from(modinverse(&k, order.value())?);
+        let k_inv_d = modinverse(&(&k % self.d), &BigUint::from(self.d))?;
+
+        let sum = hs.iter().cloned().fold(self.zero_hash(), |acc, h| acc + h);
+        let r = (BigUint::from(sum.r) * k_inv_d) % self.d;
+        let h = Hash {
+            r: r.to_u16().unwrap(),
+            g: sum.g.pow(k_inv.value()),
+            d: self.d,
+        };
+        h.debug_assert_canonical();
+        Some(h)
+    }
+
+    /**
+    Return the hash of the linear combination sum(weights[i] * x_i).
+
+    values[i] is a hash of x_i. The group element is prod(g_i^weights[i])
+    mod n, with negative weights raising the inverse of g_i, and the residue
+    is sum(weights[i] * r_i) mod d, so the result equals hashing the
+    combination of the openings, which are never needed. On a centered
+    generator the combination of the committed x_i - center is off by
+    (sum(weights) - 1) * center, which is added back. Fails if the slices
+    have different lengths or a hash is from another generator; no terms
+    give the hash of zero.
+    */
+    pub fn hash_linear_combination(
+        &self,
+        weights: &[BigInt],
+        values: &[Hash],
+    ) -> Result<Hash, crate::Error> {
+        let h = self.combine(weights, values)?;
+        if self.center.is_zero() {
+            return Ok(h);
+        }
+        let total: BigInt = weights.iter().sum();
+        Ok(h + self.hash_uncentered(&((total - 1) * &self.center), false).0)
+    }
+
+    // Combine the committed exponents of values with weights, ignoring the
+    // center
+    fn combine(&self, weights: &[BigInt], values: &[Hash]) -> Result<Hash, crate::Error> {
+        if weights.len() != values.len() {
+            return Err(HashArithError::LengthMismatch.into());
+        }
+        if !values.iter().all(|h| self.accepts(h)) {
+            return Err(HashArithError::ParamMismatch.into());
+        }
+        let d = BigInt::from(self.d);
+        let mut r = BigInt::zero();
+        let mut g = GroupElement::one(self.n.clone());
+        for (w, h) in weights.iter().zip(values) {
+            r += w * BigInt::from(h.r);
+            let base = if w.sign() == Sign::Minus {
+                h.g.inverse().ok_or(crate::Error::InverseFailed)?
+            } else {
+                h.g.clone()
+            };
+            g = g.mul(&base.pow(w.magnitude()));
+        }
+        let r = ((r % &d) + &d) % &d;
+        let h = Hash {
+            r: r.to_u16().unwrap(),
+            g,
+            d: self.d,
+        };
+        h.debug_assert_canonical();
+        Ok(h)
+    }
+
+    /**
+    Probabilistically check that openings[i] opens hashes[i] for every i.
+
+    Random 128-bit coefficients c_i are drawn and the combination
+    sum(c_i * hashes[i]) is compared with the hash of sum(c_i * openings[i]),
+    so a single modpow on the opening side checks the whole batch; the
+    residues are compared exactly beforehand since they are public. On a
+    centered generator every hash commits to openings[i] - center, so the
+    centered openings are combined and hashed without the center. An
+    invalid opening passes only if its discrepancy cancels in the
+    combination: for discrepancies without small-order components this
+    happens with probability about 2^-128, but a discrepancy of order w,
+    such as g replaced by n - g, is missed with probability about 1/w.
+    Check is_valid_element under a safe-prime generator first, or verify
+    one by one, when hashes may be crafted adversarially.
+    */
+    pub fn batch_verify_openings(&self, hashes: &[Hash], openings: &[BigInt]) -> bool {
+        if hashes.len() != openings.len() || !hashes.iter().all(|h| self.accepts(h)) {
+            return false;
+        }
+        let d = BigInt::from(self.d);
+        let offsets: Vec<BigInt> = openings.iter().map(|o| o - &self.center).collect();
+        let residues_match = hashes
+            .iter()
+            .zip(&offsets)
+            .all(|(h, o)| o.mod_floor(&d) == BigInt::from(h.r));
+        if !residues_match {
+            return false;
+        }
+
+        let mut rng = rand::thread_rng();
+        let coeffs: Vec<BigInt> = hashes
+            .iter()
+            .map(|_| BigInt::from(rng.gen_biguint(128)))
+            .collect();
+        let combined: BigInt = coeffs.iter().zip(&offsets).map(|(c, o)| c * o).sum();
+        match self.combine(&coeffs, hashes) {
+            Ok(lhs) => lhs == self.hash_uncentered(&combined, false).0,
+            Err(_) => false,
+        }
+    }
+
+    /**
+    Lazily verify a stream of (hash, opening) pairs.
+
+    Each opening is hashed again and compared with its hash, as in
+    Commitment::verify, only when the next result is pulled, so this fits
+    into iterator pipelines over unbounded sources.
+    */
+    pub fn verify_stream<'a>(
+        &'a self,
+        items: impl Iterator<Item = (Hash, BigInt)> + 'a,
+    ) -> impl Iterator<Item = bool> + 'a {
+        items.map(move |(h, opening)| self.hash(&opening) == h)
+    }
+
+    /**
+    Check that hashing a fixed value twice gives the same hash.
+
+    hash takes no per-call randomness, only the fixed a, so this is always
+    true for this scheme, for every constructor and center: equal inputs
+    give equal hashes, and a hash hides its preimage only as far as the
+    preimage is unpredictable. It exists to state that property in code; a
+    blinded variant adding fresh randomness per call would return false.
+    */
+    pub fn is_deterministic(&self) -> bool {
+        let x = BigInt::from(0x5eed_u32);
+        self.hash(&x) == self.hash(&x)
+    }
+
+    /**
+    Check whether the preimage of h lies in [-t,t], or in
+    [center-t, center+t] for a centered generator.
+    */
+    pub fn in_range(&self, h: &Hash) -> bool {
+        self.scan(h).is_some()
+    }
+
+    /**
+    Same as in_range, but without leaking where the match was found.
+
+    eval stops at the first match, so its running time tells how far the
+    preimage is from t. This instead always checks the fixed window of
+    2s+3 candidates around [-t,t], masks out those outside the range and
+    compares with constant-time equality, so the control flow does not
+    depend on the preimage. It always pays for the full window, roughly
+    twice the average cost of in_range. The modpow itself is not constant
+    time, so this is a best-effort defence.
+    */
+    pub fn in_range_ct(&self, h: &Hash) -> bool {
+        let step = i64::from(self.d);
+        let top = i64::from(self.t);
+        let width = self.modulus_len();
+        let target = fixed_width_bytes(&h.g.value, width);
+
+        let start = i64::from(self.s) * step + i64::from(h.r);
+        let mut found = Choice::from(0);
+        for k in 0..(2 * i64::from(self.s) + 3) {
+            let c = start - k * step;
+            let in_window = Choice::from(((c >= -top) & (c <= top)) as u8);
+            let candidate = fixed_width_bytes(&self.pow_small(c), width);
+            found |= in_window & candidate.ct_eq(&target);
+        }
+        found.into()
+    }
+
+    /**
+    Check whether h is the hash of any of the candidates, without revealing which.
+
+    Every candidate is hashed and compared with constant-time equality on
+    the residue and the fixed-width group element, and the flags are
+    combined without early exit, so the control flow does not depend on
+    which candidate matched. As in in_range_ct the modpow itself is not
+    constant time. A hash of another generator matches nothing.
+    */
+    pub fn matches_any(&self, h: &Hash, candidates: &[BigInt]) -> bool {
+        if !self.accepts(h) {
+            return false;
+        }
+        let width = self.modulus_len();
+        let target = fixed_width_bytes(&h.g.value, width);
+        let mut found = Choice::from(0);
+        for c in candidates {
+            let candidate = self.hash(c);
+            let same_r = candidate.r.ct_eq(&h.r);
+            let same_g = fixed_width_bytes(&candidate.g.value, width).ct_eq(&target);
+            found |= same_r & same_g;
+        }
+        found.into()
+    }
+
+    /**
+    Check with constant-time comparisons whether h is the hash of value.
+
+    This is matches_any with the single candidate value: r and the
+    fixed-width g of hash(value) are compared without early exit.
+    */
+    pub fn equals_value(&self, h: &Hash, value: &BigInt) -> bool {
+        self.matches_any(h, std::slice::from_ref(value))
+    }
+
+    /**
+    Open h to x, for selectively disclosing committed values.
+
+    Fails with Error::InvalidOpening unless x is the preimage of h, so an
+    Opening is only ever built for a value that opens its hash.
+    */
+    pub fn open(&self, h: &Hash, x: &BigInt) -> Result<Opening, crate::Error> {
+        if !self.equals_value(h, x) {
+            return Err(crate::Error::InvalidOpening);
+        }
+        Ok(Opening { value: x.clone() })
+    }
+
+    /**
+    Check that the opening o reveals the preimage of h.
+
+    The comparison is constant time as in equals_value.
+    */
+    pub fn check_opening(&self, h: &Hash, o: &Opening) -> bool {
+        self.equals_value(h, &o.value)
+    }
+
+    /**
+    Return the candidates eval checks for h, in the order they are checked.
+
+    They are the values eval may return, so on a centered generator they
+    include the center; values that do not fit in i32 are left out.
+    */
+    pub fn eval_candidates(&self, h: &Hash) -> Vec<i32> {
+        self.candidates(h.r)
+            .filter_map(|c| self.centered(c))
+            .collect()
+    }
+
+    /**
+    Return a deterministic transcript of the scan eval performs on h.
+
+    For every exponent c checked, in order and up to the first match, the
+    transcript holds c as a big-endian i32 followed by a^c mod n left-padded
+    to the byte length of n. On a centered generator c is the offset from
+    the center, x - center, not the value eval returns. It only depends on
+    the generator and h, so it can be fed into a Fiat-Shamir challenge.
+    */
+    pub fn eval_transcript(&self, h: &Hash) -> Vec<u8> {
+        let width = self.modulus_len();
+        let mut out = Vec::new();
+        for c in self.candidates(h.r) {
+            let candidate = self.pow_small(c.into());
+            out.extend_from_slice(&c.to_be_bytes());
+            out.extend_from_slice(&fixed_width_bytes(&candidate, width));
+            if candidate == h.g.value {
+                break;
+            }
+        }
+        out
+    }
+
+    /**
+    Check whether the public residue of h is r.
+
+    This is a cheap pre-filter before eval: a hash whose residue differs
+    from that of an expected value cannot open to it. Panics if r is not
+    below d.
+    */
+    pub fn has_residue(&self, h: &Hash, r: u16) -> bool {
+        assert!(r < self.d, "residue {} is not below d={}", r, self.d);
+        h.r == r
+    }
+
+    /**
+    Return the bucket of h, its public residue in 0..d.
+
+    The bucket is r = x mod d, which every hash reveals anyway, so
+    bucketing leaks nothing beyond x mod d.
+    */
+    pub fn bucket(&self, h: &Hash) -> u16 {
+        h.r
+    }
+
+    /**
+    Count the hashes per bucket, indexed by residue in 0..d.
+
+    Hashes not produced under the parameters of this generator are skipped.
+    */
+    pub fn bucket_counts(&self, hs: &[Hash]) -> Vec<usize> {
+        let mut counts = vec![0; usize::from(self.d)];
+        for h in hs.iter().filter(|h| self.accepts(h)) {
+            counts[usize::from(self.bucket(h))] += 1;
+        }
+        counts
+    }
+
+    /**
+    Count the distinct residues among hs, a coarse diversity metric.
 
-/**
-Struct for hash value of Integer Close To HRPPH.
+    Only the public residues are read, so this reveals nothing beyond the
+    values x mod d every hash already discloses.
+    */
+    pub fn distinct_residues(&self, hs: &[Hash]) -> usize {
+        hs.iter()
+            .map(|h| h.r)
+            .collect::<std::collections::HashSet<_>>()
+            .len()
+    }
 
-r: small remains for enumerating potential items
-d: small modulus for enumerating potential items
-d: collision resistant hash value
-n: big modulus for collision resistant hash
-*/
-#[derive(Debug, PartialEq, Eq)]
-pub struct Hash {
-    r: u16,
-    g: BigUint,
-    d: u16,
-    n: BigUint,
-}
+    /**
+    Return every value in [-t,t] congruent to r modulo d, in ascending order.
+
+    This is the search space of eval for any hash with residue r, i.e. all
+    that the public residue alone reveals about an in-range preimage. On a
+    centered generator r is the residue of x - center, so the values are
+    those of [center-t, center+t] whose offset from the center is congruent
+    to r, leaving out values that do not fit in i32.
+    */
+    pub fn congruent_candidates(&self, r: u16) -> Vec<i32> {
+        assert!(r < self.d, "residue {} is not below d={}", r, self.d);
+        let step: i32 = self.d.into();
+        let top: i32 = self.t.into();
+        let bottom: i32 = -top;
+
+        let lowest = bottom + (i32::from(r) - bottom).rem_euclid(step);
+        (lowest..=top)
+            .step_by(self.d.into())
+            .filter_map(|c| self.centered(c))
+            .collect()
+    }
 
-impl HRPPHICT {
     /**
-    Given a threshold and the bits of key, return a HRPPHICT generator.
+    Return how many bits of the preimage the public residue reveals.
 
-    Collision resistant hash is generated from RSA library.
-    The enumerating process is limited to no more than 200 rounds.
+    Every hash carries r = x mod d in the clear, so r is not hidden: it
+    leaks log2(d) bits about x regardless of the hiding of g.
+    */
+    pub fn residue_entropy_bits(&self) -> f64 {
+        f64::from(self.d).log2()
+    }
+
+    /**
+    Estimate how many bits of a preimage from a domain of 2^input_domain_bits
+    values stay hidden after r and g are revealed.
 
+    This is the first-order estimate input_domain_bits - log2(d), floored
+    at zero: r gives away residue_entropy_bits, and g is assumed to reveal
+    nothing beyond that, which holds for preimages outside [-t,t] under the
+    hiding of the group. Preimages inside [-t,t] are fully recovered by
+    eval, so for them nothing is hidden whatever this returns.
     */
-    pub fn new(threshold: u16, lambda: u64) -> HRPPHICT {
-        let d = if threshold <= 100 {
-            threshold
-        } else {
-            threshold / 100
-        };
+    pub fn hidden_bits(&self, input_domain_bits: u32) -> f64 {
+        (f64::from(input_domain_bits) - self.residue_entropy_bits()).max(0.0)
+    }
 
-        let mut rng = rand::thread_rng();
-        let priv_key =
-            RsaPrivateKey::new(&mut rng, lambda as usize).expect("failed to generate a key");
-        let pub_key = RsaPublicKey::from(&priv_key);
-        let mut a = rng.gen_biguint(lambda);
-        let module = BigUint::from_bytes_le(&(pub_key.n().to_bytes_le()));
-        a %= &module;
+    /**
+    Estimate the probability that two distinct values in [-t,t] collide.
 
-        HRPPHICT {
-            t: threshold,
-            d,
-            s: threshold / d,
-            a: a.clone(),
-            n: module.clone(),
+    The model treats the 2t+1 hashes as uniform in a group of 2^(b-1)
+    elements, b the bit length of n, and applies the birthday bound
+    k(k-1)/2 / 2^(b-1) with k = 2t+1, capped at one. It is conservative:
+    2^(b-1) undercounts n, and a real collision needs the order of a to
+    divide a difference of at most 2t, which is far less likely for RSA
+    moduli. For moduli of more than about a thousand bits the estimate
+    underflows to zero.
+    */
+    pub fn collision_probability(&self) -> f64 {
+        let k = 2.0 * f64::from(self.t) + 1.0;
+        let pairs = k * (k - 1.0) / 2.0;
+        let exponent = i32::try_from(self.n.bits() - 1).unwrap_or(i32::MAX);
+        (pairs * 2f64.powi(-exponent)).min(1.0)
+    }
+
+    /**
+    Check whether the group element of h lies in the subgroup of a.
+
+    When the subgroup order is known, as for new_safe_prime, this checks
+    g^order == 1, so a tampered g outside the quadratic residues is
+    rejected. For opaque RSA moduli the order is unknown and every element
+    is accepted.
+    */
+    pub fn is_valid_element(&self, h: &Hash) -> bool {
+        match &self.order {
+            Some(order) => self.accepts(h) && h.g.pow(order.value()).value.is_one(),
+            None => true,
         }
     }
 
-    pub fn hash(&self, x: &BigInt) -> Hash {
-        let c = if *x >= BigInt::zero() {
-            self.a.modpow(&(x.to_biguint().unwrap()), &(self.n))
-        } else {
-            let i = self.a.modpow(&((-x).to_biguint().unwrap()), &(self.n));
-            modinverse(&i, &(self.n)).unwrap()
-        };
-        let new_r = x % self.d;
-        let positive_new_r = if new_r < BigInt::zero() {
-            new_r + self.d
-        } else {
-            new_r
-        };
-        let u16_r = positive_new_r.to_u16().unwrap();
-        assert_eq!(BigInt::from(u16_r), positive_new_r);
-        Hash {
-            g: c.clone(),
-            r: u16_r,
-            d: self.d,
-            n: self.n.clone(),
+    /**
+    Report which operations this generator supports.
+
+    With a = 1 every hash has the same group element and commits only to
+    its residue, so neither hash nor eval is meaningful. The order is known
+    to new_safe_prime generators and found by randomizer_order for small or
+    smooth factors; generators from from_params, and new_with_escrow ones
+    whose factors are too large to factor p-1 and q-1, do not know it.
+    When it is known and at most 2t, eval can return a wrong candidate, so
+    can_eval and can_rehash are false; an unknown order is assumed large.
+    For a party holding only d and n see VerifierContext.
+    */
+    pub fn capabilities(&self) -> Capabilities {
+        let can_hash = !self.a.value().is_one();
+        let order = self
+            .order
+            .clone()
+            .or_else(|| self.randomizer_order().map(SecretUint::from));
+        let can_eval = can_hash
+            && order
+                .as_ref()
+                .is_none_or(|o| *o.value() > BigUint::from(self.t) * 2u32);
+        Capabilities {
+            can_hash,
+            can_eval,
+            knows_order: order.is_some(),
+            can_rehash: can_eval,
         }
     }
 
-    pub fn eval(&self, h: &Hash) -> (Option<i32>, bool) {
-        let step: i32 = self.d.into();
-        let top: i32 = self.t.into();
-        let bottom: i32 = -top;
+    /**
+    Return the multiplicative order of a modulo n, when the primes are known.
 
-        let mut c: i32 = (self.s * self.d) as i32 + h.r as i32;
-        if c > top {
-            c -= step;
-        };
-        while c > bottom {
-            if self.eqcheck(c, &h.g) {
-                return (Some(c), true);
+    This needs the factors kept by new_with_escrow, new_safe_prime or
+    from_primes. The order divides the Carmichael value lcm(p-1, q-1) and
+    is found by removing its prime factors while a^ord == 1 still holds.
+    p-1 and q-1 are factored by trial division, so this is limited to
+    primes whose p-1 and q-1 are smooth up to one large prime factor, as
+    with safe primes or small test primes. For random primes of a real key,
+    e.g. those of new_with_escrow, None is returned; hash_mean and
+    recover_exponent_mod_order then work modulo the Carmichael value.
+    */
+    pub fn randomizer_order(&self) -> Option<BigUint> {
+        let (p, q) = self.factors.as_ref()?;
+        let p1 = SecretUint::from(p.value() - 1u32);
+        let q1 = SecretUint::from(q.value() - 1u32);
+        let mut primes = primes::prime_factors(&mut rand::thread_rng(), p1.value())?;
+        primes.extend(primes::prime_factors(&mut rand::thread_rng(), q1.value())?);
+        primes.sort();
+        primes.dedup();
+
+        let mut order = self.carmichael()?.value().clone();
+        for f in primes {
+            while (&order % &f).is_zero() && self.pow_a(&BigInt::from(&order / &f)).is_one() {
+                order /= &f;
+            }
+        }
+        Some(order)
+    }
+
+    // The Carmichael value lcm(p-1, q-1) of n, a multiple of the order of a
+    fn carmichael(&self) -> Option<SecretUint> {
+        let (p, q) = self.factors.as_ref()?;
+        let p1 = SecretUint::from(p.value() - 1u32);
+        let q1 = SecretUint::from(q.value() - 1u32);
+        let gcd = SecretUint::from(p1.value().gcd(q1.value()));
+        Some(SecretUint::from(p1.value() / gcd.value() * q1.value()))
+    }
+
+    // The order of a when it is known or can be found, else the Carmichael
+    // value, so that exponents can still be reduced
+    fn order_bound(&self) -> Option<SecretUint> {
+        self.order
+            .clone()
+            .or_else(|| self.randomizer_order().map(SecretUint::from))
+            .or_else(|| self.carmichael())
+    }
+
+    /**
+    Find the least exponent e >= 0 with a^e == g by baby-step giant-step,
+    and return the preimage e + center it stands for.
+
+    When the order of a is known, directly or from the factors, the result
+    is the preimage modulo the order, or modulo the Carmichael value when
+    the factors are known but the order cannot be found from them. The
+    search covers e below that modulus,
+    capped at BSGS_MAX_EXPONENT, and returns None when no such e exists,
+    e.g. for large orders and preimages. With the order unknown, None is
+    also returned when e + center is negative. It takes about sqrt(bound)
+    multiplications and keeps as many group elements in a table, so this
+    is a debugging aid for small parameters only.
+    */
+    pub fn recover_exponent_mod_order(&self, h: &Hash) -> Option<BigUint> {
+        if !self.accepts(h) {
+            return None;
+        }
+        let cap = BigUint::from(BSGS_MAX_EXPONENT);
+        let order = self.order_bound();
+        let bound = match &order {
+            Some(order) => order.value().min(&cap).clone(),
+            None => cap,
+        }
+        .to_u64()
+        .unwrap();
+        let m = (bound as f64).sqrt().ceil() as u64;
+
+        let mut table = std::collections::HashMap::new();
+        let mut baby = BigUint::one();
+        for j in 0..m {
+            table.entry(baby.clone()).or_insert(j);
+            baby = (baby * self.a.value()) % &self.n;
+        }
+        let giant = self.a_inv.value().modpow(&BigUint::from(m), &self.n);
+        let mut gamma = h.g.value.clone();
+        for i in 0..m {
+            if let Some(j) = table.get(&gamma) {
+                let e = i * m + j;
+                if e >= bound {
+                    return None;
+                }
+                let x = BigInt::from(e) + &self.center;
+                return match &order {
+                    Some(order) => {
+                        let m = x.magnitude() % order.value();
+                        if x.sign() == Sign::Minus && !m.is_zero() {
+                            Some(order.value() - m)
+                        } else {
+                            Some(m)
+                        }
+                    }
+                    None => x.to_biguint(),
+                };
             }
-            c -= step;
+            gamma = (gamma * &giant) % &self.n;
+        }
+        None
+    }
+
+    /**
+    Check whether g is invertible modulo n, i.e. gcd(g, n) == 1.
+
+    Subtracting a hash inverts its group element, so this guards Sub
+    against group elements that share a factor with n.
+    */
+    pub fn is_invertible(&self, g: &BigUint) -> bool {
+        GroupElement::new(g.clone(), self.n.clone()).is_invertible()
+    }
+
+    /**
+    Return every candidate in [-t,t] matching h, in scan order.
+
+    With sound parameters at most one candidate matches. As for eval, a
+    centered generator returns the values, center included, and leaves out
+    those that do not fit in i32.
+    */
+    pub fn eval_all(&self, h: &Hash) -> Vec<i32> {
+        self.candidates(h.r)
+            .filter(|c| self.eqcheck(*c, &h.g.value))
+            .filter_map(|c| self.centered(c))
+            .collect()
+    }
+
+    /**
+    Run eval_all over every hash, for auditing parameters for ambiguity.
+
+    With the parallel feature the hashes are scanned concurrently with
+    rayon, otherwise one after another. The results keep the order of hs.
+    */
+    pub fn eval_all_batch(&self, hs: &[Hash]) -> Vec<Vec<i32>> {
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            hs.par_iter().map(|h| self.eval_all(h)).collect()
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            hs.iter().map(|h| self.eval_all(h)).collect()
+        }
+    }
+
+    /**
+    Same as eval, but fail instead of picking the first of several matches.
+
+    Two matching candidates mean a has a small order modulo n, i.e. the
+    parameters are broken; this surfaces it as Error::Ambiguous. A hash
+    without any match fails with Error::OutOfRange.
+    */
+    pub fn eval_unambiguous(&self, h: &Hash) -> Result<i32, crate::Error> {
+        let mut matches = self.eval_all(h);
+        match matches.len() {
+            0 => Err(crate::Error::OutOfRange),
+            1 => Ok(matches.remove(0)),
+            _ => Err(crate::Error::Ambiguous(matches)),
+        }
+    }
+
+    /**
+    Check that h was produced under the same d and n as this generator.
+
+    Run this on hashes from external sources before combining them, since
+    adding hashes with different parameters panics.
+    */
+    pub fn accepts(&self, h: &Hash) -> bool {
+        h.d == self.d && h.g.modulus == self.n
+    }
+
+    /**
+    Check that h is a well-formed hash under the parameters of this generator.
+
+    r must be below d, g must lie in [1, n), and d and n must be those of
+    the generator. Hashes built by this crate always pass; call this at
+    trust boundaries, e.g. after deserializing with serde, before doing
+    arithmetic, which assumes these invariants.
+    */
+    pub fn validate_hash(&self, h: &Hash) -> Result<(), crate::Error> {
+        if h.d != self.d || h.g.modulus != self.n {
+            return Err(ValidationError::ParamMismatch.into());
+        }
+        if h.r >= h.d {
+            return Err(ValidationError::ResidueOutOfRange { r: h.r, d: h.d }.into());
         }
-        (None, false)
+        if h.g.value.is_zero() || h.g.value >= h.g.modulus {
+            return Err(ValidationError::ElementOutOfRange.into());
+        }
+        Ok(())
+    }
+
+    /**
+    Check whether hashes of this generator and of other can be combined.
+
+    Hash arithmetic only needs equal d and n, so such generators may still
+    differ in a, t or center; a sum of their hashes then opens under
+    neither generator.
+    */
+    pub fn compatible_with(&self, other: &HRPPHICT) -> bool {
+        self.d == other.d && self.n == other.n
     }
 
     pub fn n(&self) -> BigUint {
         self.n.clone()
     }
 
+    /**
+    Compute a^e mod n for any exponent e.
+
+    Negative exponents raise the cached inverse of a, as hashing does, so
+    pow_a(x) is the group element of hash(x).
+    */
+    pub fn pow_a(&self, e: &BigInt) -> BigUint {
+        pow_signed(self.a.value(), self.a_inv.value(), &self.n, e)
+    }
+
+    /**
+    Return the approximate heap bytes held by the limbs of a, a_inv, n, the
+    center, and the order of a and the factors of n when they are kept.
+
+    The inline size of the struct is not included and allocator slack is
+    ignored, so this is a lower bound meant for capacity planning.
+    */
+    pub fn heap_size(&self) -> usize {
+        self.a.heap_size()
+            + self.a_inv.heap_size()
+            + limb_bytes(&self.n)
+            + limb_bytes(self.center.magnitude())
+            + self.order.as_ref().map_or(0, SecretUint::heap_size)
+            + self
+                .factors
+                .as_ref()
+                .map_or(0, |(p, q)| p.heap_size() + q.heap_size())
+    }
+
+    // Wrap a value already reduced modulo n as a group element
+    fn element(&self, value: BigUint) -> GroupElement {
+        GroupElement {
+            value,
+            modulus: self.n.clone(),
+        }
+    }
+
+    // Enumerate the candidates congruent to h.r and return the first match
+    fn scan(&self, h: &Hash) -> Option<i32> {
+        self.scan_parts(h.r, &h.g.value)
+    }
+
+    // Same as scan for a residue and group element not wrapped in a Hash
+    fn scan_parts(&self, r: u16, g: &BigUint) -> Option<i32> {
+        self.candidates(r).find(|c| self.eqcheck(*c, g))
+    }
+
+    // Candidates in [-t,t] congruent to r, from the top down. Every
+    // candidate lies in [-t,t] and fits in i32.
+    fn candidates(&self, r: u16) -> impl Iterator<Item = i32> {
+        scan_candidates(self.t.into(), self.d.into(), self.s.into(), r.into()).map(|c| c as i32)
+    }
+
+    // Byte length of n
+    fn modulus_len(&self) -> usize {
+        self.n.bits().div_ceil(8) as usize
+    }
+
     // Check if the candidate match the input
     fn eqcheck(&self, x: i32, y: &BigUint) -> bool {
-        let h = if x >= 0 {
-            self.a.modpow(&BigUint::from(x as u32), &(self.n))
-        } else {
-            let i = self.a.modpow(&BigUint::from((-x) as u32), &self.n);
-            modinverse(&i, &self.n).unwrap()
-        };
+        self.pow_small(x.into()) == *y
+    }
 
-        h == *y
+    // Compute a^x mod n for a small exponent
+    fn pow_small(&self, x: i64) -> BigUint {
+        pow_signed_small(self.a.value(), self.a_inv.value(), &self.n, x)
     }
 }
 
+/**
+Extension trait for folding an iterator of inputs into one aggregate hash.
+*/
+pub trait HashIterExt<'a>: Iterator<Item = &'a BigInt> + Sized {
+    /**
+    Hash every item with gen and add up the hashes.
+
+    An empty iterator yields gen.zero_hash().
+    */
+    fn hash_fold(self, gen: &HRPPHICT) -> Hash {
+        self.fold(gen.zero_hash(), |acc, x| acc + gen.hash(x))
+    }
+}
+
+impl<'a, I: Iterator<Item = &'a BigInt>> HashIterExt<'a> for I {}
+
 impl Add for Hash {
     type Output = Self;
 
     fn add(self, other: Self) -> Self::Output {
         assert_eq!(&self.d, &other.d);
-        assert_eq!(&self.n, &other.n);
+        self.debug_assert_canonical();
+        other.debug_assert_canonical();
 
-        Self {
-            r: (self.r + other.r) % self.d,
-            g: (self.g * other.g) % &(self.n),
+        let h = Self {
+            // Sum in u32 so residues above u16::MAX / 2 cannot overflow
+            r: ((u32::from(self.r) + u32::from(other.r)) % u32::from(self.d)) as u16,
+            g: self.g.mul(&other.g),
             d: self.d,
-            n: self.n.clone(),
-        }
+        };
+        h.debug_assert_canonical();
+        h
     }
 }
 
@@ -152,19 +1893,233 @@ impl Sub for Hash {
     type Output = Self;
 
     fn sub(self, other: Self) -> Self::Output {
+        self.debug_assert_canonical();
         self.add(other.inverse())
     }
 }
 
 impl Hash {
     fn inverse(&self) -> Hash {
-        Self {
-            r: self.d - self.r,
-            g: modinverse(&(self.g), &(self.n)).unwrap(),
+        self.checked_inverse().unwrap()
+    }
+
+    // Negate the hash, failing if g shares a factor with n
+    fn checked_inverse(&self) -> Result<Hash, crate::Error> {
+        self.debug_assert_canonical();
+        let h = Self {
+            r: (self.d - self.r) % self.d,
+            g: self.g.inverse().ok_or(crate::Error::InverseFailed)?,
             d: self.d,
-            n: self.n.clone(),
+        };
+        h.debug_assert_canonical();
+        Ok(h)
+    }
+
+    /**
+    Return the approximate heap bytes held by the limbs of g and n.
+
+    Both are roughly as long as the modulus, so a hash costs about twice
+    the modulus bytes on top of its inline size. Allocator slack is ignored.
+    */
+    pub fn heap_size(&self) -> usize {
+        limb_bytes(&self.g.value) + limb_bytes(&self.g.modulus)
+    }
+
+    /**
+    Drop d and n, keeping only what differs between hashes of a generator.
+    */
+    pub fn compact(&self) -> CompactHash {
+        CompactHash {
+            r: self.r,
+            g: self.g.value.clone(),
+        }
+    }
+
+    /**
+    Return the group element of the hash, g together with its modulus n.
+    */
+    pub fn group_element(&self) -> &GroupElement {
+        &self.g
+    }
+
+    /**
+    Return g^e mod n as a bare group element.
+
+    Unlike hash_linear_combination, which scales the committed value and
+    its residue together, this only touches g: r * e mod d is not computed,
+    so the result is not a Hash and cannot be opened with eval. A negative
+    e raises the inverse of g. Panics if e is negative and g is not
+    invertible, which never happens for hashes of a generator.
+    */
+    pub fn pow_element(&self, e: &BigInt) -> BigUint {
+        let base = if e.sign() == Sign::Minus {
+            self.g.inverse().expect("group element is not invertible")
+        } else {
+            self.g.clone()
+        };
+        base.pow(e.magnitude()).value
+    }
+
+    /**
+    Combine hashes modulo coprime n1 and n2 into one hash modulo n1*n2.
+
+    If h1 = a^x mod n1 and h2 = a^x mod n2, the result is a^x mod n1*n2, the
+    hash of x under a generator with the product modulus. This extends the
+    modulus when more room is needed. Both hashes must carry the same r and d.
+    */
+    pub fn crt_combine(h1: &Hash, h2: &Hash) -> Result<Hash, crate::Error> {
+        if h1.r != h2.r || h1.d != h2.d {
+            return Err(CrtError::ResidueMismatch.into());
+        }
+        let (n1, n2) = (&h1.g.modulus, &h2.g.modulus);
+        let n1_inv = modinverse(n1, n2).ok_or(CrtError::NotCoprime)?;
+
+        let g1 = h1.g.value.to_bigint().unwrap();
+        let diff = (h2.g.value.to_bigint().unwrap() - &g1) * n1_inv.to_bigint().unwrap();
+        let m2 = n2.to_bigint().unwrap();
+        let k = ((diff % &m2) + &m2) % &m2;
+        let g = (g1 + k * n1.to_bigint().unwrap()).to_biguint().unwrap();
+
+        Ok(Hash {
+            r: h1.r,
+            g: GroupElement::new(g, n1 * n2),
+            d: h1.d,
+        })
+    }
+
+    /**
+    Return r mapped into the balanced range (-d/2, d/2].
+
+    r is stored in [0, d); residues above d/2 are shifted down by d so the
+    residue can be read as a signed quantity.
+    */
+    pub fn balanced_residue(&self) -> i32 {
+        let r = i32::from(self.r);
+        let d = i32::from(self.d);
+        if 2 * r > d {
+            r - d
+        } else {
+            r
+        }
+    }
+
+    // Check r < d, g < n and g != 0, only in debug builds
+    fn debug_assert_canonical(&self) {
+        debug_assert!(
+            self.r < self.d,
+            "residue {} is not below d={}",
+            self.r,
+            self.d
+        );
+        debug_assert!(
+            self.g.value < self.g.modulus,
+            "group element is not reduced modulo n"
+        );
+        debug_assert!(!self.g.value.is_zero(), "group element is zero");
+    }
+}
+
+/**
+Return the index pairs (i, j), i < j, of generators sharing both a and n.
+
+Generators with the same a and n produce the same hashes, so a collision
+means the setup reused parameters that were meant to be independent. The
+pairs are listed in increasing order of i, then j.
+*/
+pub fn detect_parameter_collisions(gens: &[HRPPHICT]) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+    for (i, x) in gens.iter().enumerate() {
+        for (j, y) in gens.iter().enumerate().skip(i + 1) {
+            if x.a == y.a && x.n == y.n {
+                pairs.push((i, j));
+            }
         }
     }
+    pairs
+}
+
+// Big-endian bytes of x left-padded to width
+fn fixed_width_bytes(x: &BigUint, width: usize) -> Vec<u8> {
+    let bytes = x.to_bytes_be();
+    let mut out = vec![0u8; width.saturating_sub(bytes.len())];
+    out.extend_from_slice(&bytes);
+    out
+}
+
+// Map a salt to the exponent offset SHA-256(salt)
+fn salt_offset(salt: &[u8]) -> BigInt {
+    BigInt::from_bytes_be(Sign::Plus, &Sha256::digest(salt))
+}
+
+// Derive d from the threshold: t itself up to 100, t/100 beyond
+fn default_d<T: Copy + PartialOrd + From<u8> + Div<Output = T>>(threshold: T) -> T {
+    if threshold <= T::from(100) {
+        threshold
+    } else {
+        threshold / T::from(100)
+    }
+}
+
+// Residue x mod d in [0, d) of the exponent with the given magnitude and
+// sign, folded from |x| mod d so no negative remainder is formed
+fn fold_residue(magnitude: &BigUint, negative: bool, d: u32) -> u32 {
+    let m = (magnitude % d).to_u32().unwrap();
+    if negative {
+        (d - m) % d
+    } else {
+        m
+    }
+}
+
+// Candidates in [-t,t] congruent to r modulo d, from t/d*d + r down. The
+// bounds are computed in i64, so no u32 t, d, s and r can overflow.
+fn scan_candidates(t: i64, d: i64, s: i64, r: i64) -> impl Iterator<Item = i64> {
+    let mut c = s * d + r;
+    if c > t {
+        c -= d;
+    };
+    (-t..=c).rev().step_by(d as usize)
+}
+
+// Compute a^e mod n, raising the cached inverse a_inv for negative e
+fn pow_signed(a: &BigUint, a_inv: &BigUint, n: &BigUint, e: &BigInt) -> BigUint {
+    if e.sign() == Sign::Minus {
+        a_inv.modpow(e.magnitude(), n)
+    } else {
+        a.modpow(e.magnitude(), n)
+    }
+}
+
+// Same as pow_signed for an exponent that fits in i64, without a BigInt
+fn pow_signed_small(a: &BigUint, a_inv: &BigUint, n: &BigUint, x: i64) -> BigUint {
+    if x >= 0 {
+        a.modpow(&BigUint::from(x as u64), n)
+    } else {
+        a_inv.modpow(&BigUint::from(x.unsigned_abs()), n)
+    }
+}
+
+// Bytes taken by the 64-bit limbs of x
+fn limb_bytes(x: &BigUint) -> usize {
+    x.iter_u64_digits().len() * std::mem::size_of::<u64>()
+}
+
+// Generate an RSA key whose modulus has lambda bits
+fn gen_key<R: CryptoRng + RngCore>(rng: &mut R, lambda: u64) -> RsaPrivateKey {
+    RsaPrivateKey::new(rng, lambda as usize).expect("failed to generate a key")
+}
+
+// Take the modulus of priv_key and a random base reduced modulo it
+fn gen_randomizer<R: CryptoRng + RngCore>(
+    rng: &mut R,
+    priv_key: &RsaPrivateKey,
+    lambda: u64,
+) -> (BigUint, BigUint) {
+    let pub_key = RsaPublicKey::from(priv_key);
+    let mut a = rng.gen_biguint(lambda);
+    let module = BigUint::from_bytes_le(&(pub_key.n().to_bytes_le()));
+    a %= &module;
+    (a, module)
 }
 
 fn egcd(a: &BigInt, b: &BigInt) -> (BigInt, BigInt, BigInt) {
@@ -191,7 +2146,7 @@ fn mod_inverse_test() {
     let mut bits = 128;
     let mut rng = rand::thread_rng();
     while bits < 5000 {
-        let a = rng.gen_biguint(bits as u64);
+        let a = rng.gen_biguint(bits);
         let generator = HRPPHICT::new(128, bits);
         let n = generator.n();
         let y = modinverse(&a, &n).unwrap();
@@ -199,3 +2154,64 @@ fn mod_inverse_test() {
         bits *= 2;
     }
 }
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "residue 5 is not below d=5")]
+fn malformed_residue_test() {
+    let valid = Hash {
+        r: 1,
+        g: GroupElement {
+            value: BigUint::from(2u32),
+            modulus: BigUint::from(15u32),
+        },
+        d: 5,
+    };
+    let malformed = Hash {
+        r: 5,
+        g: GroupElement {
+            value: BigUint::from(2u32),
+            modulus: BigUint::from(15u32),
+        },
+        d: 5,
+    };
+    let _ = valid + malformed;
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "group element is not reduced modulo n")]
+fn malformed_group_element_test() {
+    let malformed = Hash {
+        r: 1,
+        g: GroupElement {
+            value: BigUint::from(17u32),
+            modulus: BigUint::from(15u32),
+        },
+        d: 5,
+    };
+    let _ = malformed.inverse();
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "group element is zero")]
+fn malformed_zero_element_test() {
+    let valid = Hash {
+        r: 1,
+        g: GroupElement {
+            value: BigUint::from(2u32),
+            modulus: BigUint::from(15u32),
+        },
+        d: 5,
+    };
+    let malformed = Hash {
+        r: 1,
+        g: GroupElement {
+            value: BigUint::zero(),
+            modulus: BigUint::from(15u32),
+        },
+        d: 5,
+    };
+    let _ = valid - malformed;
+}
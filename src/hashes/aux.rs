@@ -0,0 +1,49 @@
+use super::{Hash, PublicResidue, HRPPHICT};
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::ToPrimitive;
+
+/**
+Struct for a hash extended with an informational residue.
+
+hash: the hash of the input, as returned by hash
+aux: the residue of the input modulo d2, if with_aux_residue set d2
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtendedHash {
+    pub hash: Hash,
+    pub aux: Option<PublicResidue>,
+}
+
+impl HRPPHICT {
+    /**
+    Keep a secondary residue modulo d2 next to every hash_extended result.
+
+    The residue r2 = x mod d2 is purely informational: eval, the arithmetic
+    and the encodings ignore it. It is revealed in the clear like r, so the
+    pair leaks x mod lcm(d, d2), up to log2(d) + log2(d2) bits. Panics if d2
+    is zero.
+    */
+    pub fn with_aux_residue(mut self, d2: u16) -> HRPPHICT {
+        assert!(d2 >= 1, "d2 must be at least 1");
+        self.aux_d = Some(d2);
+        self
+    }
+
+    /**
+    Hash x and, with an auxiliary modulus d2, compute r2 = x mod d2.
+
+    r2 is the least non-negative residue of the same value r is taken of,
+    x - center for a centered generator.
+    */
+    pub fn hash_extended(&self, x: &BigInt) -> ExtendedHash {
+        let aux = self.aux_d.map(|d2| {
+            let r2 = (x - &self.center).mod_floor(&BigInt::from(d2));
+            PublicResidue::new(r2.to_u16().unwrap(), d2)
+        });
+        ExtendedHash {
+            hash: self.hash(x),
+            aux,
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use super::secret::SecretUint;
+use super::{GroupElement, Hash, HRPPHICT};
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+/**
+Struct for a table of a^v mod n for the small values v in 0..=max_value.
+
+powers: powers[v] = a^v mod n
+a: randomizer of the generator the table was built for
+n: modulus of the generator the table was built for
+
+The powers and the copy of a are secret-sensitive, like a in the
+generator, and are wiped on drop with the zeroize feature.
+*/
+#[derive(Debug, Clone)]
+pub struct SmallValueCache {
+    powers: Vec<SecretUint>,
+    a: SecretUint,
+    n: BigUint,
+}
+
+impl HRPPHICT {
+    /**
+    Precompute the group elements of every value in 0..=max_value.
+
+    The table is filled with one multiplication per entry, so building it is
+    far cheaper than hashing each value once, and every later hash of a
+    cached value is a lookup.
+    */
+    pub fn build_small_value_cache(&self, max_value: u16) -> SmallValueCache {
+        let mut powers = Vec::with_capacity(usize::from(max_value) + 1);
+        let mut power = BigUint::one() % &self.n;
+        for _ in 0..=max_value {
+            let next = (&power * self.a.value()) % &self.n;
+            powers.push(SecretUint::from(power));
+            power = next;
+        }
+        SmallValueCache {
+            powers,
+            a: self.a.clone(),
+            n: self.n.clone(),
+        }
+    }
+}
+
+impl SmallValueCache {
+    /**
+    Return the hash of v under gen from the table.
+
+    The result equals gen.hash(&BigInt::from(v)). Returns None if v is
+    beyond the table, gen is not the generator the table was built for or
+    gen is centered.
+    */
+    pub fn hash(&self, gen: &HRPPHICT, v: u16) -> Option<Hash> {
+        if gen.a != self.a || gen.n != self.n || !gen.center.is_zero() {
+            return None;
+        }
+        let g = self.powers.get(usize::from(v))?;
+        let h = Hash {
+            r: v % gen.d,
+            g: GroupElement {
+                value: g.value().clone(),
+                modulus: self.n.clone(),
+            },
+            d: gen.d,
+        };
+        h.debug_assert_canonical();
+        Some(h)
+    }
+
+    /**
+    Return the largest value held by the table.
+    */
+    pub fn max_value(&self) -> u16 {
+        (self.powers.len() - 1) as u16
+    }
+}
@@ -0,0 +1,62 @@
+use super::{Hash, HRPPHICT};
+use num_bigint::BigInt;
+use std::ops::{Add, Sub};
+
+/**
+Struct for a hash of a Gaussian integer re + im*i.
+
+Both parts are hashed separately with the same generator, so addition and
+subtraction work componentwise.
+
+re: hash of the real part
+im: hash of the imaginary part
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComplexHash {
+    pub re: Hash,
+    pub im: Hash,
+}
+
+impl HRPPHICT {
+    /**
+    Hash the Gaussian integer re + im*i, one part at a time.
+    */
+    pub fn hash_complex(&self, re: &BigInt, im: &BigInt) -> ComplexHash {
+        ComplexHash {
+            re: self.hash(re),
+            im: self.hash(im),
+        }
+    }
+
+    /**
+    Recover both parts of a complex hash.
+
+    Returns None unless the real and the imaginary part each lie in [-t,t],
+    or in [center-t, center+t] for a centered generator, as for eval.
+    */
+    pub fn eval_complex(&self, h: &ComplexHash) -> Option<(i32, i32)> {
+        Some((self.eval(&h.re).0?, self.eval(&h.im).0?))
+    }
+}
+
+impl Add for ComplexHash {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self::Output {
+        ComplexHash {
+            re: self.re + other.re,
+            im: self.im + other.im,
+        }
+    }
+}
+
+impl Sub for ComplexHash {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self::Output {
+        ComplexHash {
+            re: self.re - other.re,
+            im: self.im - other.im,
+        }
+    }
+}
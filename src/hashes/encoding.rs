@@ -0,0 +1,206 @@
+use super::{fixed_width_bytes, GroupElement, Hash, HRPPHICT};
+use base64ct::{Base64UrlUnpadded, Encoding};
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+use std::io::{self, Read, Write};
+
+/// Version byte written in front of every encoded hash.
+pub const HASH_ENCODING_VERSION: u8 = 1;
+
+/**
+Cause of Error::Decode, returned when decoding a hash or parameters.
+*/
+#[derive(Debug, thiserror::Error)]
+pub enum DecodeError {
+    /// The leading version byte is not a known encoding version
+    #[error("unsupported encoding version {0}")]
+    UnsupportedVersion(u8),
+    /// The input ended before all fields were read
+    #[error("input is truncated")]
+    Truncated,
+    /// Bytes were left over after the last field
+    #[error("input has trailing bytes")]
+    TrailingBytes,
+    /// The fields were read but do not form a valid hash
+    #[error("malformed hash: {0}")]
+    Malformed(&'static str),
+    /// The text is not valid unpadded URL-safe base64
+    #[error("invalid base64")]
+    InvalidBase64,
+    /// The text is not a valid JWK-like JSON object
+    #[cfg(feature = "json")]
+    #[error("invalid JWK-like key: {0}")]
+    Json(#[from] serde_json::Error),
+    /// Reading the input failed
+    #[error("failed to read hash: {0}")]
+    Io(#[from] io::Error),
+}
+
+impl Hash {
+    /**
+    Encode the hash as bytes.
+
+    Layout of version 1, all integers big-endian:
+    version (u8), r (u16), d (u16), then g and n each as a u32 length
+    followed by that many bytes.
+    */
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = vec![HASH_ENCODING_VERSION];
+        out.extend_from_slice(&self.r.to_be_bytes());
+        out.extend_from_slice(&self.d.to_be_bytes());
+        put_biguint(&mut out, &self.g.value);
+        put_biguint(&mut out, &self.g.modulus);
+        out
+    }
+
+    /**
+    Decode a hash written by to_bytes, dispatching on the version byte.
+    */
+    pub fn from_bytes(bytes: &[u8]) -> Result<Hash, crate::Error> {
+        let (version, rest) = bytes.split_first().ok_or(DecodeError::Truncated)?;
+        match *version {
+            1 => Ok(decode_v1(rest)?),
+            v => Err(DecodeError::UnsupportedVersion(v).into()),
+        }
+    }
+
+    /**
+    Encode to_bytes as URL-safe base64 without padding, for text protocols.
+    */
+    pub fn to_base64(&self) -> String {
+        Base64UrlUnpadded::encode_string(&self.to_bytes())
+    }
+
+    /**
+    Decode a hash written by to_base64.
+    */
+    pub fn from_base64(text: &str) -> Result<Hash, crate::Error> {
+        let bytes = Base64UrlUnpadded::decode_vec(text).map_err(|_| DecodeError::InvalidBase64)?;
+        Hash::from_bytes(&bytes)
+    }
+
+    /**
+    Write the encoding of to_bytes to w.
+    */
+    pub fn save<W: Write>(&self, mut w: W) -> io::Result<()> {
+        w.write_all(&self.to_bytes())
+    }
+
+    /**
+    Read a hash written by save, consuming r to the end.
+    */
+    pub fn load<R: Read>(mut r: R) -> Result<Hash, crate::Error> {
+        let mut bytes = Vec::new();
+        r.read_to_end(&mut bytes).map_err(DecodeError::from)?;
+        Hash::from_bytes(&bytes)
+    }
+
+    /**
+    Encode the hash with a length fixed by the modulus.
+
+    Layout of version 1, all integers big-endian:
+    version (u8), r (u16), d (u16), then g and n each left-padded to the
+    byte length of n. Every hash of one generator encodes to
+    serialized_hash_len bytes, so framed readers can preallocate.
+    */
+    pub fn to_fixed_bytes(&self) -> Vec<u8> {
+        let width = self.g.modulus.bits().div_ceil(8) as usize;
+        let mut out = vec![HASH_ENCODING_VERSION];
+        out.extend_from_slice(&self.r.to_be_bytes());
+        out.extend_from_slice(&self.d.to_be_bytes());
+        out.extend_from_slice(&fixed_width_bytes(&self.g.value, width));
+        out.extend_from_slice(&fixed_width_bytes(&self.g.modulus, width));
+        out
+    }
+
+    /**
+    Decode a hash written by to_fixed_bytes.
+    */
+    pub fn from_fixed_bytes(bytes: &[u8]) -> Result<Hash, crate::Error> {
+        let (version, mut cursor) = bytes.split_first().ok_or(DecodeError::Truncated)?;
+        if *version != HASH_ENCODING_VERSION {
+            return Err(DecodeError::UnsupportedVersion(*version).into());
+        }
+        let r = take_u16(&mut cursor)?;
+        let d = take_u16(&mut cursor)?;
+        if cursor.len() % 2 != 0 {
+            return Err(DecodeError::Malformed("g and n must have the same width").into());
+        }
+        let width = cursor.len() / 2;
+        let g = BigUint::from_bytes_be(take(&mut cursor, width)?);
+        let n = BigUint::from_bytes_be(take(&mut cursor, width)?);
+        Ok(check_fields(r, g, d, n)?)
+    }
+}
+
+impl HRPPHICT {
+    /**
+    Return the length of to_fixed_bytes for every hash of this generator.
+
+    It is one version byte, two bytes each for r and d and the byte length
+    of n for each of g and n.
+    */
+    pub fn serialized_hash_len(&self) -> usize {
+        5 + 2 * self.modulus_len()
+    }
+}
+
+fn decode_v1(bytes: &[u8]) -> Result<Hash, DecodeError> {
+    let mut cursor = bytes;
+    let r = take_u16(&mut cursor)?;
+    let d = take_u16(&mut cursor)?;
+    let g = take_biguint(&mut cursor)?;
+    let n = take_biguint(&mut cursor)?;
+    if !cursor.is_empty() {
+        return Err(DecodeError::TrailingBytes);
+    }
+    check_fields(r, g, d, n)
+}
+
+// Build a hash from decoded fields, rejecting non-canonical ones
+fn check_fields(r: u16, g: BigUint, d: u16, n: BigUint) -> Result<Hash, DecodeError> {
+    if d == 0 || r >= d {
+        return Err(DecodeError::Malformed("r must lie in [0, d)"));
+    }
+    if n <= BigUint::one() {
+        return Err(DecodeError::Malformed("n must be at least 2"));
+    }
+    if g.is_zero() || g >= n {
+        return Err(DecodeError::Malformed("g must lie in [1, n)"));
+    }
+    Ok(Hash {
+        r,
+        g: GroupElement {
+            value: g,
+            modulus: n,
+        },
+        d,
+    })
+}
+
+// Append x as a u32 big-endian length followed by its big-endian bytes
+pub(super) fn put_biguint(out: &mut Vec<u8>, x: &BigUint) {
+    let bytes = x.to_bytes_be();
+    out.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+    out.extend_from_slice(&bytes);
+}
+
+pub(super) fn take<'a>(cursor: &mut &'a [u8], len: usize) -> Result<&'a [u8], DecodeError> {
+    if cursor.len() < len {
+        return Err(DecodeError::Truncated);
+    }
+    let (head, tail) = cursor.split_at(len);
+    *cursor = tail;
+    Ok(head)
+}
+
+pub(super) fn take_u16(cursor: &mut &[u8]) -> Result<u16, DecodeError> {
+    let bytes = take(cursor, 2)?;
+    Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+pub(super) fn take_biguint(cursor: &mut &[u8]) -> Result<BigUint, DecodeError> {
+    let len = take(cursor, 4)?;
+    let len = u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize;
+    Ok(BigUint::from_bytes_be(take(cursor, len)?))
+}
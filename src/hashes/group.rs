@@ -0,0 +1,97 @@
+use super::{egcd, modinverse};
+use num_bigint::{BigUint, ToBigInt};
+use num_traits::One;
+
+/**
+Struct for an element of the multiplicative group modulo n.
+
+This is the collision resistant part of a hash, kept apart from the
+residue r used for enumeration. Operations only combine elements with the
+same modulus and always return reduced values.
+
+value: the element, reduced modulo modulus
+modulus: big modulus of the group
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GroupElement {
+    #[cfg_attr(feature = "serde", serde(rename = "g"))]
+    pub(super) value: BigUint,
+    #[cfg_attr(feature = "serde", serde(rename = "n"))]
+    pub(super) modulus: BigUint,
+}
+
+impl GroupElement {
+    /**
+    Return value reduced modulo modulus as a group element.
+    */
+    pub fn new(value: BigUint, modulus: BigUint) -> GroupElement {
+        GroupElement {
+            value: value % &modulus,
+            modulus,
+        }
+    }
+
+    /**
+    Return the identity of the group modulo modulus.
+    */
+    pub fn one(modulus: BigUint) -> GroupElement {
+        GroupElement {
+            value: BigUint::one(),
+            modulus,
+        }
+    }
+
+    pub fn value(&self) -> &BigUint {
+        &self.value
+    }
+
+    pub fn modulus(&self) -> &BigUint {
+        &self.modulus
+    }
+
+    /**
+    Multiply two elements of the same group.
+
+    Panics if the moduli differ.
+    */
+    pub fn mul(&self, other: &GroupElement) -> GroupElement {
+        assert_eq!(&self.modulus, &other.modulus);
+        GroupElement {
+            value: (&self.value * &other.value) % &self.modulus,
+            modulus: self.modulus.clone(),
+        }
+    }
+
+    /**
+    Raise the element to the power e.
+    */
+    pub fn pow(&self, e: &BigUint) -> GroupElement {
+        GroupElement {
+            value: self.value.modpow(e, &self.modulus),
+            modulus: self.modulus.clone(),
+        }
+    }
+
+    /**
+    Return the inverse of the element, or None if it shares a factor with
+    the modulus.
+    */
+    pub fn inverse(&self) -> Option<GroupElement> {
+        modinverse(&self.value, &self.modulus).map(|value| GroupElement {
+            value,
+            modulus: self.modulus.clone(),
+        })
+    }
+
+    /**
+    Check whether the element is a unit, i.e. coprime to the modulus.
+    */
+    pub fn is_invertible(&self) -> bool {
+        let (gcd, _, _) = egcd(
+            &self.value.to_bigint().unwrap(),
+            &self.modulus.to_bigint().unwrap(),
+        );
+        gcd.is_one()
+    }
+}
@@ -0,0 +1,62 @@
+use super::{DecodeError, PublicParams, HRPPHICT};
+use base64ct::{Base64UrlUnpadded, Encoding};
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+
+/// Key type written in the kty field of a JWK-like key.
+pub const JWK_KEY_TYPE: &str = "HRPPH-ICT";
+
+// JSON object of a JWK-like key: the big integers as unpadded base64url
+// big-endian bytes, the threshold and residue modulus as numbers
+#[derive(Serialize, Deserialize)]
+struct JwkLike {
+    kty: String,
+    n: String,
+    a: String,
+    t: u16,
+    d: u16,
+}
+
+impl HRPPHICT {
+    /**
+    Build a generator from a JWK-like JSON object.
+
+    The object has kty "HRPPH-ICT", n and a as unpadded base64url
+    big-endian bytes and the numbers t and d; s is derived as t / d. Missing
+    fields and bad JSON fail with DecodeError::Json, bad base64 with
+    DecodeError::InvalidBase64, and the parameters are validated as in
+    from_params.
+    */
+    pub fn from_jwk_like(json: &str) -> Result<HRPPHICT, crate::Error> {
+        let key: JwkLike = serde_json::from_str(json).map_err(DecodeError::from)?;
+        if key.kty != JWK_KEY_TYPE {
+            return Err(DecodeError::Malformed("kty must be HRPPH-ICT").into());
+        }
+        HRPPHICT::from_params(PublicParams {
+            t: key.t,
+            d: key.d,
+            s: key.t.checked_div(key.d).unwrap_or(0),
+            a: decode_biguint(&key.a)?,
+            n: decode_biguint(&key.n)?,
+        })
+    }
+
+    /**
+    Write the parameters as the JWK-like JSON object read by from_jwk_like.
+    */
+    pub fn to_jwk_like(&self) -> String {
+        let key = JwkLike {
+            kty: JWK_KEY_TYPE.to_string(),
+            n: Base64UrlUnpadded::encode_string(&self.n.to_bytes_be()),
+            a: Base64UrlUnpadded::encode_string(&self.a.value().to_bytes_be()),
+            t: self.t,
+            d: self.d,
+        };
+        serde_json::to_string(&key).expect("key serializes to JSON")
+    }
+}
+
+fn decode_biguint(text: &str) -> Result<BigUint, DecodeError> {
+    let bytes = Base64UrlUnpadded::decode_vec(text).map_err(|_| DecodeError::InvalidBase64)?;
+    Ok(BigUint::from_bytes_be(&bytes))
+}
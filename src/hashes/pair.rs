@@ -0,0 +1,91 @@
+use super::{GroupElement, ParamError, ParamViolation, HRPPHICT};
+use num_bigint::BigInt;
+use std::collections::HashMap;
+
+/**
+Struct for a generator committing to pairs (x, y) as a1^x * a2^y mod n.
+
+It combines two generators sharing n. The pair hash is binding only if
+no small relation a1^u = a2^v with |u|, |v| <= 2t holds; for independent
+random a1 and a2 modulo an RSA modulus such a relation is as hard to find
+as the order of the group. Whoever generated n, or knows the discrete logs
+between a1 and a2, can open a pair hash to other pairs.
+
+first: generator of x, providing a1 and the residue modulus of x
+second: generator of y, providing a2 and the residue modulus of y
+*/
+#[derive(Debug, Clone)]
+pub struct PairGen {
+    first: HRPPHICT,
+    second: HRPPHICT,
+}
+
+/**
+Struct for hash value of a pair.
+
+rx: x mod d of the first generator
+ry: y mod d of the second generator
+g: a1^x * a2^y mod n
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PairHash {
+    rx: u16,
+    ry: u16,
+    g: GroupElement,
+}
+
+impl HRPPHICT {
+    /**
+    Pair this generator with other, which must share the modulus n.
+
+    Fails with ParamViolation::ModulusMismatch otherwise. Centers set with
+    new_centered are ignored: pairs are hashed and recovered as they are.
+    */
+    pub fn pair_with(&self, other: &HRPPHICT) -> Result<PairGen, crate::Error> {
+        if self.n != other.n {
+            return Err(ParamError {
+                violations: vec![ParamViolation::ModulusMismatch],
+            }
+            .into());
+        }
+        Ok(PairGen {
+            first: self.clone(),
+            second: other.clone(),
+        })
+    }
+}
+
+impl PairGen {
+    /**
+    Hash the pair (x, y).
+    */
+    pub fn hash(&self, x: &BigInt, y: &BigInt) -> PairHash {
+        let hx = self.first.hash_uncentered(x, false).0;
+        let hy = self.second.hash_uncentered(y, false).0;
+        PairHash {
+            rx: hx.r,
+            ry: hy.r,
+            g: hx.g.mul(&hy.g),
+        }
+    }
+
+    /**
+    Recover (x, y) when x lies within the threshold of the first generator
+    and y within that of the second.
+
+    The powers a2^y of every candidate y are tabled first, then g * a1^-x
+    is looked up for every candidate x, so the cost is the sum of the two
+    scans rather than their product.
+    */
+    pub fn eval(&self, h: &PairHash) -> Option<(i32, i32)> {
+        let table: HashMap<_, _> = self
+            .second
+            .candidates(h.ry)
+            .map(|y| (self.second.pow_small(y.into()), y))
+            .collect();
+        self.first.candidates(h.rx).find_map(|x| {
+            let rest = (&h.g.value * self.first.pow_small(-i64::from(x))) % &self.first.n;
+            table.get(&rest).map(|y| (x, *y))
+        })
+    }
+}
@@ -0,0 +1,319 @@
+use super::encoding::{put_biguint, take_biguint, take_u16};
+use super::primes::is_probable_prime;
+use super::secret::SecretUint;
+use super::{default_d, modinverse, DecodeError, HASH_ENCODING_VERSION, HRPPHICT};
+use num_bigint::{BigInt, BigUint, RandBigInt};
+use num_traits::{One, Zero};
+use std::convert::TryFrom;
+use std::fmt;
+
+/**
+Struct for the parameters of an Integer Close To HRPPH generator.
+
+t: threshold used for judging if the input of hash is within [-t,t]
+d: small modulus for enumerating potential items
+s: half of enumerating test cases, equals t / d
+a: random number for randomizing the collision resistant hash value
+n: big modulus for collision resistant hash
+
+With the serde feature a and n are written as decimal strings, so JSON
+configs stay readable and diffable.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PublicParams {
+    pub t: u16,
+    pub d: u16,
+    pub s: u16,
+    #[cfg_attr(feature = "serde", serde(with = "decimal"))]
+    pub a: BigUint,
+    #[cfg_attr(feature = "serde", serde(with = "decimal"))]
+    pub n: BigUint,
+}
+
+// Serde helpers writing a BigUint as a decimal string
+#[cfg(feature = "serde")]
+mod decimal {
+    use num_bigint::BigUint;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(x: &BigUint, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&x.to_str_radix(10))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BigUint, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        BigUint::parse_bytes(s.as_bytes(), 10)
+            .ok_or_else(|| de::Error::custom("expected a decimal integer"))
+    }
+}
+
+/**
+Struct for a readable dump of the parameters of a generator.
+
+The big integers are rendered in decimal so the dump can be pasted into
+support tickets; n_bits is the bit length of n.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParamDescription {
+    pub t: u16,
+    pub d: u16,
+    pub s: u16,
+    pub a: String,
+    pub n: String,
+    pub n_bits: u64,
+}
+
+impl fmt::Display for ParamDescription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "t = {}", self.t)?;
+        writeln!(f, "d = {}", self.d)?;
+        writeln!(f, "s = {}", self.s)?;
+        writeln!(f, "a = {}", self.a)?;
+        writeln!(f, "n = {}", self.n)?;
+        write!(f, "n bits = {}", self.n_bits)
+    }
+}
+
+/**
+A single constraint broken by a PublicParams.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParamViolation {
+    /// d must lie in [1, t]
+    InvalidD { d: u16, t: u16 },
+    /// s must equal t / d
+    InvalidS { s: u16, expected: u16 },
+    /// n must be at least 2
+    ModulusTooSmall,
+    /// a must be reduced modulo n
+    RandomizerNotReduced,
+    /// a must be invertible modulo n, otherwise negative inputs cannot be hashed
+    RandomizerNotInvertible,
+    /// A factor supplied to from_primes is not prime
+    FactorNotPrime(BigUint),
+    /// The factors supplied to from_primes must differ
+    EqualFactors,
+    /// Generators combined with pair_with must share n
+    ModulusMismatch,
+}
+
+/**
+Cause of Error::ParamInvalid, listing every violated constraint.
+*/
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("invalid parameters: {}", join_violations(.violations))]
+pub struct ParamError {
+    pub violations: Vec<ParamViolation>,
+}
+
+// Render violations separated by semicolons
+fn join_violations(violations: &[ParamViolation]) -> String {
+    violations
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+impl fmt::Display for ParamViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamViolation::InvalidD { d, t } => write!(f, "d={} must be in [1, t={}]", d, t),
+            ParamViolation::InvalidS { s, expected } => {
+                write!(f, "s={} must equal t/d={}", s, expected)
+            }
+            ParamViolation::ModulusTooSmall => write!(f, "n must be at least 2"),
+            ParamViolation::RandomizerNotReduced => write!(f, "a must be less than n"),
+            ParamViolation::RandomizerNotInvertible => write!(f, "a must be invertible modulo n"),
+            ParamViolation::FactorNotPrime(p) => write!(f, "factor {} is not prime", p),
+            ParamViolation::EqualFactors => write!(f, "p and q must differ"),
+            ParamViolation::ModulusMismatch => write!(f, "generators must share n"),
+        }
+    }
+}
+
+impl PublicParams {
+    /**
+    Check every constraint on the parameters and collect all violations.
+
+    Every violated constraint is reported, not only the first one found.
+    This is meant for debugging malformed params, not as a timing defence:
+    the invertibility of a is only checked for a valid n, and that check
+    takes time depending on a and n.
+    */
+    pub fn validate(&self) -> Result<(), crate::Error> {
+        let mut violations = Vec::new();
+
+        let d_valid = self.d >= 1 && self.d <= self.t;
+        if !d_valid {
+            violations.push(ParamViolation::InvalidD {
+                d: self.d,
+                t: self.t,
+            });
+        }
+        let expected = self.t.checked_div(self.d).unwrap_or(0);
+        if self.s != expected {
+            violations.push(ParamViolation::InvalidS {
+                s: self.s,
+                expected,
+            });
+        }
+
+        let n_valid = self.n > BigUint::one();
+        if !n_valid {
+            violations.push(ParamViolation::ModulusTooSmall);
+        }
+        if self.a >= self.n {
+            violations.push(ParamViolation::RandomizerNotReduced);
+        }
+        if n_valid && modinverse(&self.a, &self.n).is_none() {
+            violations.push(ParamViolation::RandomizerNotInvertible);
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(ParamError { violations }.into())
+        }
+    }
+}
+
+impl HRPPHICT {
+    /**
+    Rebuild a generator from its parameters.
+
+    The parameters are validated first and every violated constraint is
+    reported in the returned error.
+    */
+    pub fn from_params(params: PublicParams) -> Result<HRPPHICT, crate::Error> {
+        params.validate()?;
+        let a_inv = modinverse(&params.a, &params.n).unwrap();
+        Ok(HRPPHICT {
+            t: params.t,
+            d: params.d,
+            s: params.s,
+            a: SecretUint::from(params.a),
+            n: params.n,
+            a_inv: SecretUint::from(a_inv),
+            order: None,
+            factors: None,
+            center: BigInt::zero(),
+            aux_d: None,
+        })
+    }
+
+    /**
+    Build a generator with the modulus n = p*q from externally supplied primes.
+
+    Both factors are checked with Miller-Rabin and must differ; every failed
+    check is reported. d and s follow the threshold as in new, and a is
+    sampled uniformly among the units modulo n. This lets audited primes be
+    used, but whoever supplied them can break collision resistance.
+    */
+    pub fn from_primes(threshold: u16, p: &BigUint, q: &BigUint) -> Result<HRPPHICT, crate::Error> {
+        let mut rng = rand::thread_rng();
+        let mut violations = Vec::new();
+        for factor in [p, q] {
+            if !is_probable_prime(&mut rng, factor) {
+                violations.push(ParamViolation::FactorNotPrime(factor.clone()));
+            }
+        }
+        if p == q {
+            violations.push(ParamViolation::EqualFactors);
+        }
+        if !violations.is_empty() {
+            return Err(ParamError { violations }.into());
+        }
+
+        let n = p * q;
+        let a = loop {
+            let a = rng.gen_biguint_below(&n);
+            if !a.is_one() && modinverse(&a, &n).is_some() {
+                break a;
+            }
+        };
+        let d = default_d(threshold);
+        let mut generator = HRPPHICT::from_params(PublicParams {
+            t: threshold,
+            d,
+            s: threshold.checked_div(d).unwrap_or(0),
+            a,
+            n,
+        })?;
+        generator.factors = Some((SecretUint::new(p), SecretUint::new(q)));
+        Ok(generator)
+    }
+
+    /**
+    Describe the parameters of this generator in a stable, readable form.
+    */
+    pub fn describe(&self) -> ParamDescription {
+        ParamDescription {
+            t: self.t,
+            d: self.d,
+            s: self.s,
+            a: self.a.value().to_str_radix(10),
+            n: self.n.to_str_radix(10),
+            n_bits: self.n.bits(),
+        }
+    }
+
+    /**
+    Export the parameters of this generator.
+    */
+    pub fn params(&self) -> PublicParams {
+        PublicParams {
+            t: self.t,
+            d: self.d,
+            s: self.s,
+            a: self.a.value().clone(),
+            n: self.n.clone(),
+        }
+    }
+}
+
+/**
+Encode the parameters with the same layout as the Hash encoding.
+
+Layout of version 1, all integers big-endian:
+version (u8), t (u16), d (u16), s (u16), then a and n each as a u32
+length followed by that many bytes.
+*/
+impl From<&PublicParams> for Vec<u8> {
+    fn from(params: &PublicParams) -> Vec<u8> {
+        let mut out = vec![HASH_ENCODING_VERSION];
+        out.extend_from_slice(&params.t.to_be_bytes());
+        out.extend_from_slice(&params.d.to_be_bytes());
+        out.extend_from_slice(&params.s.to_be_bytes());
+        put_biguint(&mut out, &params.a);
+        put_biguint(&mut out, &params.n);
+        out
+    }
+}
+
+/**
+Decode parameters encoded by From<&PublicParams>, validating every field.
+*/
+impl TryFrom<&[u8]> for PublicParams {
+    type Error = crate::Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let (version, mut cursor) = bytes.split_first().ok_or(DecodeError::Truncated)?;
+        if *version != HASH_ENCODING_VERSION {
+            return Err(DecodeError::UnsupportedVersion(*version).into());
+        }
+        let params = PublicParams {
+            t: take_u16(&mut cursor)?,
+            d: take_u16(&mut cursor)?,
+            s: take_u16(&mut cursor)?,
+            a: take_biguint(&mut cursor)?,
+            n: take_biguint(&mut cursor)?,
+        };
+        if !cursor.is_empty() {
+            return Err(DecodeError::TrailingBytes.into());
+        }
+        params.validate()?;
+        Ok(params)
+    }
+}
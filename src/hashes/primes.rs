@@ -0,0 +1,131 @@
+use num_bigint::{BigUint, RandBigInt};
+use num_traits::{One, Zero};
+use rand::{CryptoRng, RngCore};
+
+// Rounds of Miller-Rabin, giving an error below 2^-80 for random inputs
+const MILLER_RABIN_ROUNDS: usize = 40;
+
+// Largest divisor tried when factoring by trial division
+const TRIAL_DIVISION_BOUND: u32 = 1 << 20;
+
+// Odd primes used to discard candidates before Miller-Rabin
+const SMALL_PRIMES: [u32; 24] = [
+    3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+];
+
+// Check whether n is probably prime with trial division and Miller-Rabin
+pub(super) fn is_probable_prime<R: CryptoRng + RngCore>(rng: &mut R, n: &BigUint) -> bool {
+    let two = BigUint::from(2u32);
+    if *n < two {
+        return false;
+    }
+    if *n == two {
+        return true;
+    }
+    if (n % 2u32).is_zero() {
+        return false;
+    }
+    for p in SMALL_PRIMES {
+        if *n == BigUint::from(p) {
+            return true;
+        }
+        if (n % p).is_zero() {
+            return false;
+        }
+    }
+
+    let n_minus_one = n - 1u32;
+    let k = n_minus_one.trailing_zeros().unwrap();
+    let m = &n_minus_one >> k;
+    'witness: for _ in 0..MILLER_RABIN_ROUNDS {
+        let a = rng.gen_biguint_range(&two, &n_minus_one);
+        let mut x = a.modpow(&m, n);
+        if x.is_one() || x == n_minus_one {
+            continue;
+        }
+        for _ in 1..k {
+            x = x.modpow(&two, n);
+            if x == n_minus_one {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+// Generate a prime of bits bits with its two top bits set
+pub(super) fn gen_prime<R: CryptoRng + RngCore>(rng: &mut R, bits: u64) -> BigUint {
+    assert!(bits >= 3, "primes need at least 3 bits");
+    loop {
+        let mut p = rng.gen_biguint(bits);
+        p.set_bit(bits - 1, true);
+        p.set_bit(bits - 2, true);
+        p.set_bit(0, true);
+        if is_probable_prime(rng, &p) {
+            return p;
+        }
+    }
+}
+
+// Generate a safe prime p = 2q + 1 of bits bits with its two top bits set,
+// returning (p, q)
+pub(super) fn gen_safe_prime<R: CryptoRng + RngCore>(rng: &mut R, bits: u64) -> (BigUint, BigUint) {
+    // No safe prime of 4 or 5 bits has its two top bits set
+    assert!(bits >= 6, "safe primes need at least 6 bits");
+    loop {
+        let mut q = rng.gen_biguint(bits - 1);
+        q.set_bit(bits - 2, true);
+        q.set_bit(bits - 3, true);
+        q.set_bit(0, true);
+        let p = (&q << 1) + 1u32;
+        // q and p = 2q + 1 must both avoid every small factor
+        let composite = |x: &BigUint, s: u32| (x % s).is_zero() && *x != BigUint::from(s);
+        if SMALL_PRIMES
+            .iter()
+            .any(|&s| composite(&q, s) || composite(&p, s))
+        {
+            continue;
+        }
+        if is_probable_prime(rng, &q) && is_probable_prime(rng, &p) {
+            return (p, q);
+        }
+    }
+}
+
+// Distinct prime factors of n found by trial division, or None if the
+// cofactor left beyond the trial bound is composite
+pub(super) fn prime_factors<R: CryptoRng + RngCore>(
+    rng: &mut R,
+    n: &BigUint,
+) -> Option<Vec<BigUint>> {
+    let mut rest = n.clone();
+    let mut factors = Vec::new();
+    let mut f = 2u32;
+    while f < TRIAL_DIVISION_BOUND && BigUint::from(f) * f <= rest {
+        if (&rest % f).is_zero() {
+            factors.push(BigUint::from(f));
+            while (&rest % f).is_zero() {
+                rest /= f;
+            }
+        }
+        f += if f == 2 { 1 } else { 2 };
+    }
+    if rest.is_one() {
+        return Some(factors);
+    }
+    if !is_probable_prime(rng, &rest) {
+        return None;
+    }
+    factors.push(rest);
+    Some(factors)
+}
+
+// Exact primality of a small n by trial division
+pub(super) fn is_small_prime(n: u16) -> bool {
+    let n = u32::from(n);
+    n >= 2
+        && (2..n)
+            .take_while(|f| f * f <= n)
+            .all(|f| !n.is_multiple_of(f))
+}
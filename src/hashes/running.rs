@@ -0,0 +1,70 @@
+use super::{Hash, HashArithError, HRPPHICT};
+
+/**
+Struct for a running sum of hashes, checked against a generator over time.
+
+Every operation checks that the incoming hash has the d and n of the
+accumulated one, so a hash of another generator is rejected instead of
+making the addition panic.
+
+acc: the hash of the sum of the values added so far
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunningCommitment {
+    acc: Hash,
+}
+
+impl RunningCommitment {
+    /**
+    Start an empty running sum, i.e. the hash of zero, for gen.
+    */
+    pub fn new(gen: &HRPPHICT) -> RunningCommitment {
+        RunningCommitment {
+            acc: gen.zero_hash(),
+        }
+    }
+
+    /**
+    Add h to the running sum.
+    */
+    pub fn add(&mut self, h: &Hash) -> Result<(), crate::Error> {
+        self.check(h)?;
+        self.acc = self.acc.clone() + h.clone();
+        Ok(())
+    }
+
+    /**
+    Subtract h from the running sum, failing with InverseFailed if h has
+    no inverse.
+    */
+    pub fn sub(&mut self, h: &Hash) -> Result<(), crate::Error> {
+        self.check(h)?;
+        self.acc = self.acc.clone() + h.checked_inverse()?;
+        Ok(())
+    }
+
+    /**
+    Check whether the running sum lies in [-t,t] of gen.
+
+    A generator with other parameters than the running sum gives false.
+    */
+    pub fn check_in_range(&self, gen: &HRPPHICT) -> bool {
+        gen.accepts(&self.acc) && gen.in_range(&self.acc)
+    }
+
+    /**
+    Return the accumulated hash.
+    */
+    pub fn hash(&self) -> &Hash {
+        &self.acc
+    }
+
+    // Reject hashes with other parameters than the running sum
+    fn check(&self, h: &Hash) -> Result<(), crate::Error> {
+        if h.d == self.acc.d && h.g.modulus == self.acc.g.modulus {
+            Ok(())
+        } else {
+            Err(HashArithError::ParamMismatch.into())
+        }
+    }
+}
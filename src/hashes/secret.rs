@@ -0,0 +1,107 @@
+use num_bigint::BigUint;
+#[cfg(feature = "zeroize")]
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/**
+Struct for a secret-sensitive integer of a generator.
+
+These are the values a party that only verifies must not learn: the
+order of a and the factors of n, either of which breaks collision
+resistance, and a, a_inv and the cached powers of a, which let anyone
+produce hashes. With the zeroize feature the limbs are overwritten when
+the value is dropped. value only lends the integer out, so arithmetic
+runs on it in place; temporaries derived from it are not tracked.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct SecretUint(BigUint);
+
+impl SecretUint {
+    pub(super) fn new(x: &BigUint) -> SecretUint {
+        SecretUint(x.clone())
+    }
+
+    pub(super) fn from_rsa(x: &rsa::BigUint) -> SecretUint {
+        let mut bytes = x.to_bytes_le();
+        let secret = SecretUint(BigUint::from_bytes_le(&bytes));
+        wipe(&mut bytes);
+        secret
+    }
+
+    pub(super) fn value(&self) -> &BigUint {
+        &self.0
+    }
+
+    // Bytes taken by the 64-bit limbs
+    pub(super) fn heap_size(&self) -> usize {
+        super::limb_bytes(&self.0)
+    }
+}
+
+impl From<BigUint> for SecretUint {
+    fn from(x: BigUint) -> SecretUint {
+        SecretUint(x)
+    }
+}
+
+// Clear a temporary copy of the limbs, with the zeroize feature
+#[cfg(feature = "zeroize")]
+pub(super) fn wipe(bytes: &mut [u8]) {
+    bytes.zeroize();
+}
+
+#[cfg(not(feature = "zeroize"))]
+pub(super) fn wipe(_: &mut [u8]) {}
+
+#[cfg(all(test, feature = "zeroize"))]
+thread_local! {
+    static WIPED: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+// num-bigint gives no mutable access to its limbs, but clearing a bit
+// writes the limb in place and only pops limbs once the top one is zero,
+// so clearing from the lowest bit up overwrites the buffer before it is
+// released
+#[cfg(feature = "zeroize")]
+impl Zeroize for SecretUint {
+    fn zeroize(&mut self) {
+        #[cfg(test)]
+        WIPED.with(|w| w.set(w.get() + 1));
+        for bit in 0..self.0.bits() {
+            self.0.set_bit(bit, false);
+        }
+        std::hint::black_box(&self.0);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for SecretUint {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl ZeroizeOnDrop for SecretUint {}
+
+#[cfg(feature = "zeroize")]
+#[test]
+fn zeroize_clears_value() {
+    let mut x = SecretUint::new(&BigUint::from(3u32).pow(500));
+    x.zeroize();
+    assert_eq!(x.value(), &BigUint::from(0u32));
+}
+
+#[cfg(feature = "zeroize")]
+#[test]
+fn drop_wipes_generator_and_cache() {
+    let generator = super::HRPPHICT::new_safe_prime(100, 64);
+    let cache = generator.build_small_value_cache(10);
+    assert!(cache.hash(&generator, 7).is_some());
+    let before = WIPED.with(|w| w.get());
+    drop(cache);
+    // 11 cached powers and the copy of a
+    assert_eq!(WIPED.with(|w| w.get()) - before, 12);
+    drop(generator);
+    // a, a_inv, the order and both factors
+    assert_eq!(WIPED.with(|w| w.get()) - before, 17);
+}
@@ -0,0 +1,82 @@
+use super::{GroupElement, Hash};
+use std::fmt;
+
+/**
+Struct for the part of a hash that may be disclosed.
+
+r: small remains for enumerating potential items
+d: small modulus for enumerating potential items
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublicResidue {
+    r: u16,
+    d: u16,
+}
+
+/**
+Struct for the part of a hash that must stay secret, g together with n.
+
+Debug does not print the element, so it cannot leak through logs.
+*/
+#[derive(Clone, PartialEq, Eq)]
+pub struct SecretGroupElement(GroupElement);
+
+impl PublicResidue {
+    pub(super) fn new(r: u16, d: u16) -> PublicResidue {
+        PublicResidue { r, d }
+    }
+
+    pub fn r(&self) -> u16 {
+        self.r
+    }
+
+    pub fn d(&self) -> u16 {
+        self.d
+    }
+}
+
+impl SecretGroupElement {
+    /**
+    Expose the group element.
+    */
+    pub fn element(&self) -> &GroupElement {
+        &self.0
+    }
+}
+
+impl fmt::Debug for SecretGroupElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretGroupElement(..)")
+    }
+}
+
+impl Hash {
+    /**
+    Split the hash along its disclosure boundary.
+
+    The residue r and d can be revealed in protocols that only leak r; the
+    group element g and n stay secret. join recombines both parts.
+    */
+    pub fn split(self) -> (PublicResidue, SecretGroupElement) {
+        (
+            PublicResidue {
+                r: self.r,
+                d: self.d,
+            },
+            SecretGroupElement(self.g),
+        )
+    }
+
+    /**
+    Recombine the parts produced by split.
+    */
+    pub fn join(public: PublicResidue, secret: SecretGroupElement) -> Hash {
+        let h = Hash {
+            r: public.r,
+            g: secret.0,
+            d: public.d,
+        };
+        h.debug_assert_canonical();
+        h
+    }
+}
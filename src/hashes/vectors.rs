@@ -0,0 +1,68 @@
+use super::{default_d, Hash, HRPPHICT};
+use num_bigint::{BigInt, RandBigInt};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+/// Threshold of the generator behind the test vectors.
+pub const TEST_VECTOR_THRESHOLD: u16 = 1000;
+/// Modulus bits of the generator behind the test vectors.
+pub const TEST_VECTOR_LAMBDA: u64 = 512;
+
+impl HRPPHICT {
+    /**
+    Return the generator behind the test vectors of seed.
+
+    The RSA key and the randomizer are drawn from ChaCha20 seeded with seed,
+    with TEST_VECTOR_THRESHOLD and TEST_VECTOR_LAMBDA, so the same seed gives
+    the same generator. It is only meant for interoperability testing.
+    */
+    pub fn test_vector_generator(seed: [u8; 32]) -> HRPPHICT {
+        let mut rng = ChaCha20Rng::from_seed(seed);
+        HRPPHICT::new_from_rng(
+            TEST_VECTOR_THRESHOLD,
+            default_d(TEST_VECTOR_THRESHOLD),
+            TEST_VECTOR_LAMBDA,
+            &mut rng,
+        )
+        .0
+    }
+
+    /**
+    Produce count deterministic (input, hash) pairs from seed.
+
+    The hashes are made by test_vector_generator(seed). The inputs cycle
+    through small positive, small negative, big positive and big negative
+    values, drawn from the same ChaCha20 stream after the generator, so
+    both in-range and out-of-range behaviour is covered.
+    */
+    pub fn test_vectors(seed: [u8; 32], count: usize) -> Vec<(BigInt, Hash)> {
+        let mut rng = ChaCha20Rng::from_seed(seed);
+        let generator = HRPPHICT::new_from_rng(
+            TEST_VECTOR_THRESHOLD,
+            default_d(TEST_VECTOR_THRESHOLD),
+            TEST_VECTOR_LAMBDA,
+            &mut rng,
+        )
+        .0;
+        let t = i32::from(TEST_VECTOR_THRESHOLD);
+        (0..count)
+            .map(|i| {
+                let x = match i % 4 {
+                    0 => BigInt::from(rng.gen_range(0..=t)),
+                    1 => BigInt::from(rng.gen_range(-t..0)),
+                    2 => rng.gen_biguint(TEST_VECTOR_LAMBDA).into(),
+                    _ => -BigInt::from(rng.gen_biguint(TEST_VECTOR_LAMBDA)),
+                };
+                let h = generator.hash(&x);
+                (x, h)
+            })
+            .collect()
+    }
+
+    /**
+    Check that every vector hashes to its recorded hash under this generator.
+    */
+    pub fn verify_test_vectors(&self, vectors: &[(BigInt, Hash)]) -> bool {
+        vectors.iter().all(|(x, h)| self.hash(x) == *h)
+    }
+}
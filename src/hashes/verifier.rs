@@ -0,0 +1,77 @@
+use super::{fixed_width_bytes, Hash, PublicParams};
+use num_bigint::BigUint;
+use subtle::ConstantTimeEq;
+
+/**
+Struct for a verifier that only combines and compares hashes.
+
+It holds d and n but neither a nor the threshold, so it cannot hash
+values or run eval: it can only add, subtract and compare hashes handed
+to it, e.g. checking that a received hash equals the sum of others.
+
+d: small modulus for enumerating potential items
+n: big modulus for collision resistant hash
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifierContext {
+    d: u16,
+    n: BigUint,
+}
+
+impl VerifierContext {
+    pub fn new(d: u16, n: BigUint) -> VerifierContext {
+        VerifierContext { d, n }
+    }
+
+    /**
+    Keep only d and n of the parameters of a generator.
+    */
+    pub fn from_params(params: &PublicParams) -> VerifierContext {
+        VerifierContext::new(params.d, params.n.clone())
+    }
+
+    /**
+    Add two hashes, failing with ParamMismatch if either is not under d and n.
+    */
+    pub fn add(&self, a: &Hash, b: &Hash) -> Result<Hash, crate::Error> {
+        self.check(a)?;
+        self.check(b)?;
+        Ok(a.clone() + b.clone())
+    }
+
+    /**
+    Subtract b from a, failing with ParamMismatch if either is not under d
+    and n, or with InverseFailed if b has no inverse.
+    */
+    pub fn sub(&self, a: &Hash, b: &Hash) -> Result<Hash, crate::Error> {
+        self.check(a)?;
+        self.check(b)?;
+        Ok(a.clone() + b.checked_inverse()?)
+    }
+
+    /**
+    Check whether a and b are the same hash under d and n.
+
+    r and the fixed-width g are compared in constant time. Hashes under
+    other parameters never compare equal.
+    */
+    pub fn compare(&self, a: &Hash, b: &Hash) -> bool {
+        if self.check(a).is_err() || self.check(b).is_err() {
+            return false;
+        }
+        let width = self.n.bits().div_ceil(8) as usize;
+        let same_r = a.r.ct_eq(&b.r);
+        let same_g =
+            fixed_width_bytes(&a.g.value, width).ct_eq(&fixed_width_bytes(&b.g.value, width));
+        (same_r & same_g).into()
+    }
+
+    // Reject hashes not produced under d and n
+    fn check(&self, h: &Hash) -> Result<(), crate::Error> {
+        if h.d == self.d && h.g.modulus == self.n {
+            Ok(())
+        } else {
+            Err(super::HashArithError::ParamMismatch.into())
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use super::secret::SecretUint;
+use super::{
+    default_d, fold_residue, gen_key, gen_randomizer, modinverse, pow_signed, pow_signed_small,
+    scan_candidates, GroupElement, HRPPHICT, RSA_MIN_LAMBDA,
+};
+use num_bigint::{BigInt, BigUint, Sign};
+use std::ops::{Add, Sub};
+
+/**
+Struct for Integer Close to HRPPH generator with thresholds beyond u16.
+
+It follows HRPPHICT but widens t, d and s to u32 and runs eval over i64,
+so thresholds up to u32::MAX can be used. Hashing, the eval scan and the
+derivation of d are shared with HRPPHICT.
+
+t: threshold used for judging if the input of hash is within [-t,t]
+d: small modulus for enumerating potential items
+s: half of enumerating test cases, equals t / d
+a: random number for randomizing the collision resistant hash value
+n: big modulus for collision resistant hash
+a_inv: inverse of a modulo n, cached for hashing negative inputs
+
+As for HRPPHICT, a and a_inv are wiped on drop with the zeroize feature.
+*/
+#[derive(Debug, Clone)]
+pub struct HRPPHICTWide {
+    t: u32,
+    d: u32,
+    s: u32,
+    a: SecretUint,
+    n: BigUint,
+    a_inv: SecretUint,
+}
+
+/**
+Struct for hash value of the wide Integer Close To HRPPH.
+
+r: small remains for enumerating potential items
+g: collision resistant hash value, an element of the group modulo n
+d: small modulus for enumerating potential items
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WideHash {
+    r: u32,
+    g: GroupElement,
+    d: u32,
+}
+
+impl HRPPHICTWide {
+    /**
+    Given a threshold and the bits of key, return a HRPPHICTWide generator.
+
+    d is derived from the threshold in the same way as HRPPHICT::new, and
+    lambda below the RSA minimum falls back to the same small modulus.
+    */
+    pub fn new(threshold: u32, lambda: u64) -> HRPPHICTWide {
+        let d = default_d(threshold);
+        if lambda < RSA_MIN_LAMBDA {
+            let small = HRPPHICT::new_small_modulus(1, 1, lambda);
+            return HRPPHICTWide {
+                t: threshold,
+                d,
+                s: threshold / d,
+                a: small.a,
+                n: small.n,
+                a_inv: small.a_inv,
+            };
+        }
+        let mut rng = rand::thread_rng();
+        let priv_key = gen_key(&mut rng, lambda);
+        let (a, n) = gen_randomizer(&mut rng, &priv_key, lambda);
+        let a_inv = modinverse(&a, &n).expect("randomizer is not invertible");
+
+        HRPPHICTWide {
+            t: threshold,
+            d,
+            s: threshold / d,
+            a: SecretUint::from(a),
+            n,
+            a_inv: SecretUint::from(a_inv),
+        }
+    }
+
+    pub fn hash(&self, x: &BigInt) -> WideHash {
+        WideHash {
+            r: fold_residue(x.magnitude(), x.sign() == Sign::Minus, self.d),
+            g: GroupElement {
+                value: pow_signed(self.a.value(), self.a_inv.value(), &self.n, x),
+                modulus: self.n.clone(),
+            },
+            d: self.d,
+        }
+    }
+
+    pub fn eval(&self, h: &WideHash) -> (Option<i64>, bool) {
+        let found =
+            scan_candidates(self.t.into(), self.d.into(), self.s.into(), h.r.into()).find(|c| {
+                pow_signed_small(self.a.value(), self.a_inv.value(), &self.n, *c) == h.g.value
+            });
+        match found {
+            Some(c) => (Some(c), true),
+            None => (None, false),
+        }
+    }
+
+    /**
+    Check that h was produced under the same d and n as this generator.
+
+    Adding hashes with different parameters panics, as for HRPPHICT::accepts.
+    */
+    pub fn accepts(&self, h: &WideHash) -> bool {
+        h.d == self.d && h.g.modulus == self.n
+    }
+
+    pub fn n(&self) -> BigUint {
+        self.n.clone()
+    }
+}
+
+impl Add for WideHash {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self::Output {
+        assert_eq!(&self.d, &other.d);
+
+        // Sum in u64 so two residues close to u32::MAX cannot overflow
+        let r = (u64::from(self.r) + u64::from(other.r)) % u64::from(self.d);
+        Self {
+            r: r as u32,
+            g: self.g.mul(&other.g),
+            d: self.d,
+        }
+    }
+}
+
+impl Sub for WideHash {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self::Output {
+        self.add(other.inverse())
+    }
+}
+
+impl WideHash {
+    fn inverse(&self) -> WideHash {
+        Self {
+            r: (self.d - self.r) % self.d,
+            g: self.g.inverse().unwrap(),
+            d: self.d,
+        }
+    }
+}
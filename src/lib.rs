@@ -1 +1,4 @@
-pub mod hashes;
\ No newline at end of file
+mod error;
+pub mod hashes;
+
+pub use error::Error;
@@ -1,8 +1,20 @@
 #[cfg(test)]
 mod tests {
-    use hrpph_ict::hashes::HRPPHICT;
+    use hrpph_ict::hashes::{
+        detect_parameter_collisions, Capabilities, Commitment, ComplexHash, CrtError, DecodeError,
+        GroupElement, HRPPHICTWide, Hash, HashArithError, HashIterExt, Opening, ParamViolation,
+        PublicParams, RunningCommitment, ValidationError, VerifierContext, HRPPHICT,
+        LAMBDA_128BIT_SECURITY, LAMBDA_256BIT_SECURITY, RANGE_HASHES_MAX_THRESHOLD,
+        SAFE_PRIME_MIN_LAMBDA,
+    };
+    use hrpph_ict::Error;
     use num_bigint::{BigInt, BigUint, RandBigInt, ToBigInt};
+    use num_integer::Integer;
+    use num_traits::{One, Zero};
     use rand::{rngs::ThreadRng, Rng};
+    use rsa::traits::PublicKeyParts;
+    use std::convert::TryFrom;
+    use std::time::{Duration, UNIX_EPOCH};
 
     #[test]
     fn gen_hash_small_positive_input() {
@@ -41,6 +53,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::useless_conversion)]
     fn hash_homomorphic_add() {
         let mut bits = 128;
         let mut rng = rand::thread_rng();
@@ -65,6 +78,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::useless_conversion)]
     fn hash_homomorphic_sub() {
         let mut bits = 128;
         let mut rng = rand::thread_rng();
@@ -87,7 +101,2373 @@ mod tests {
             bits *= 2;
         }
     }
+
+    #[test]
+    fn hash_sum_matches_fold() {
+        let mut rng = rand::thread_rng();
+        let t: u16 = rng.gen();
+        let bits = 256;
+        let generator = HRPPHICT::new(t, bits);
+        let xs = vec![
+            gen_input(&mut rng, t, true, true, bits),
+            gen_input(&mut rng, t, false, true, bits),
+            gen_input(&mut rng, t, true, false, bits),
+            gen_input(&mut rng, t, false, false, bits),
+        ];
+        let folded = xs
+            .iter()
+            .map(|x| generator.hash(x))
+            .reduce(|acc, h| acc + h)
+            .unwrap();
+        assert_eq!(generator.hash_sum(&xs), folded);
+        assert_eq!(generator.hash_sum(&[]), generator.hash(&BigInt::from(0)));
+    }
+
+    #[test]
+    fn eval_bigint_matches_eval() {
+        let mut rng = rand::thread_rng();
+        let t: u16 = rng.gen();
+        let bits = 256;
+        let generator = HRPPHICT::new(t, bits);
+        for big in [false, true] {
+            for positive in [false, true] {
+                let h = generator.hash(&gen_input(&mut rng, t, big, positive, bits));
+                let (y, result) = generator.eval(&h);
+                let (z, big_result) = generator.eval_bigint(&h);
+                assert_eq!(result, big_result);
+                assert_eq!(y.map(BigInt::from), z);
+            }
+        }
+    }
+
+    #[test]
+    fn from_params_round_trip() {
+        let mut rng = rand::thread_rng();
+        let t: u16 = rng.gen();
+        let bits = 256;
+        let generator = HRPPHICT::new(t, bits);
+        let rebuilt = HRPPHICT::from_params(generator.params()).unwrap();
+        let x = gen_input(&mut rng, t, false, false, bits);
+        assert_eq!(generator.hash(&x), rebuilt.hash(&x));
+        assert_eq!(
+            rebuilt.eval(&rebuilt.hash(&x)),
+            generator.eval(&generator.hash(&x))
+        );
+    }
+
+    #[test]
+    fn from_params_reports_all_violations() {
+        let params = PublicParams {
+            t: 10,
+            d: 20,
+            s: 5,
+            a: BigUint::from(33u32),
+            n: BigUint::from(15u32),
+        };
+        let Err(Error::ParamInvalid(err)) = HRPPHICT::from_params(params) else {
+            panic!("parameters were accepted");
+        };
+        assert_eq!(
+            err.violations,
+            vec![
+                ParamViolation::InvalidD { d: 20, t: 10 },
+                ParamViolation::InvalidS { s: 5, expected: 0 },
+                ParamViolation::RandomizerNotReduced,
+                ParamViolation::RandomizerNotInvertible,
+            ]
+        );
+
+        let params = PublicParams {
+            t: 10,
+            d: 0,
+            s: 1,
+            a: BigUint::from(0u32),
+            n: BigUint::from(1u32),
+        };
+        let Err(Error::ParamInvalid(err)) = HRPPHICT::from_params(params) else {
+            panic!("parameters were accepted");
+        };
+        assert_eq!(
+            err.violations,
+            vec![
+                ParamViolation::InvalidD { d: 0, t: 10 },
+                ParamViolation::InvalidS { s: 1, expected: 0 },
+                ParamViolation::ModulusTooSmall,
+            ]
+        );
+    }
+
+    #[test]
+    fn hash_fold_over_range() {
+        let generator = HRPPHICT::new(1000, 256);
+        let values: Vec<BigInt> = (-20..=35).map(BigInt::from).collect();
+        let sum: BigInt = values.iter().sum();
+        assert_eq!(values.iter().hash_fold(&generator), generator.hash(&sum));
+        assert_eq!(
+            values[..0].iter().hash_fold(&generator),
+            generator.zero_hash()
+        );
+        assert_eq!(generator.zero_hash(), generator.hash(&BigInt::from(0)));
+    }
+
+    #[test]
+    fn wide_threshold_recovery() {
+        let mut rng = rand::thread_rng();
+        let t: u32 = rng.gen_range(95_000..105_000);
+        let generator = HRPPHICTWide::new(t, 256);
+        let bound = i64::from(t);
+        for x in [0, 1, -1, bound, -bound, rng.gen_range(-bound..bound)] {
+            let (y, result) = generator.eval(&generator.hash(&BigInt::from(x)));
+            assert!(result);
+            assert_eq!(y, Some(x));
+        }
+        let (_, result) = generator.eval(&generator.hash(&BigInt::from(bound + 1)));
+        assert!(!result);
+        let (_, result) = generator.eval(&generator.hash(&BigInt::from(-bound - 1)));
+        assert!(!result);
+    }
+
+    #[test]
+    fn wide_hash_homomorphic() {
+        let mut rng = rand::thread_rng();
+        let generator = HRPPHICTWide::new(100_000, 256);
+        for _ in 0..10 {
+            let x = BigInt::from(rng.gen_range(-200_000..200_000));
+            let y = BigInt::from(rng.gen_range(-200_000..200_000));
+            assert_eq!(
+                generator.hash(&(&x + &y)),
+                generator.hash(&x) + generator.hash(&y)
+            );
+            assert_eq!(
+                generator.hash(&(&x - &y)),
+                generator.hash(&x) - generator.hash(&y)
+            );
+        }
+    }
+
+    #[test]
+    fn wide_accepts_matching_hashes_only() {
+        let generator = HRPPHICTWide::new(100_000, 256);
+        let other = HRPPHICTWide::new(100_000, 256);
+        let x = BigInt::from(-54_321);
+        assert!(generator.accepts(&generator.hash(&x)));
+        assert!(generator.clone().accepts(&generator.hash(&x)));
+        assert!(!generator.accepts(&other.hash(&x)));
+    }
+
+    #[test]
+    fn wide_small_modulus() {
+        for lambda in [48u64, 24] {
+            let generator = HRPPHICTWide::new(100_000, lambda);
+            assert_eq!(generator.n().bits(), lambda);
+            let h = generator.hash(&BigInt::from(-60_000)) + generator.hash(&BigInt::from(15));
+            assert_eq!(generator.eval(&h).0, Some(-59_985));
+        }
+    }
+
+    #[test]
+    fn accepts_matching_hashes_only() {
+        let generator = HRPPHICT::new(1000, 256);
+        let other_n = HRPPHICT::new(1000, 256);
+        let mut params = generator.params();
+        params.t = 5000;
+        params.d = 50;
+        params.s = 100;
+        let other_d = HRPPHICT::from_params(params).unwrap();
+        let x = BigInt::from(42);
+        assert!(generator.accepts(&generator.hash(&x)));
+        assert!(!generator.accepts(&other_n.hash(&x)));
+        assert!(!generator.accepts(&other_d.hash(&x)));
+    }
+
+    #[test]
+    fn security_presets() {
+        assert_eq!(LAMBDA_128BIT_SECURITY, 3072);
+        assert_eq!(LAMBDA_256BIT_SECURITY, 15360);
+        let generator = HRPPHICT::preset_128bit_security(1000);
+        assert_eq!(generator.n().bits(), LAMBDA_128BIT_SECURITY);
+    }
+
+    #[test]
+    fn escrowed_key_matches_modulus() {
+        let (generator, priv_key) = HRPPHICT::new_with_escrow(1000, 256);
+        let escrowed = BigUint::from_bytes_be(&priv_key.n().to_bytes_be());
+        assert_eq!(escrowed, generator.n());
+    }
+
+    #[test]
+    fn hash_small_matches_hash() {
+        let mut rng = rand::thread_rng();
+        let t: u16 = rng.gen();
+        let generator = HRPPHICT::new(t, 256);
+        let bound = i32::from(t);
+        for x in [0, 1, -1, bound, -bound, rng.gen_range(-bound..=bound)] {
+            assert_eq!(generator.hash_small(x), generator.hash(&BigInt::from(x)));
+        }
+    }
+
+    #[test]
+    fn congruent_candidates_match_eval_candidates() {
+        let mut rng = rand::thread_rng();
+        let t: u16 = rng.gen_range(1..5000);
+        let generator = HRPPHICT::new(t, 256);
+        let bound = i32::from(t);
+        let d = i32::from(generator.params().d);
+        for x in [0, bound, -bound, rng.gen_range(-bound..=bound)] {
+            let h = generator.hash_small(x);
+            let mut scanned = generator.eval_candidates(&h);
+            assert!(scanned.contains(&x));
+            scanned.sort();
+            let expected: Vec<i32> = (-bound..=bound)
+                .filter(|c| (c - x).rem_euclid(d) == 0)
+                .collect();
+            let residue = x.rem_euclid(d) as u16;
+            assert_eq!(generator.congruent_candidates(residue), expected);
+            assert_eq!(scanned, expected);
+        }
+    }
+
+    #[test]
+    fn eval_recovers_both_bounds() {
+        let mut rng = rand::thread_rng();
+        let t: u16 = rng.gen_range(1..=u16::MAX);
+        let generator = HRPPHICT::new(t, 256);
+        let bound = i32::from(t);
+        for x in [bound, -bound] {
+            assert_eq!(generator.eval(&generator.hash_small(x)), (Some(x), true));
+        }
+        for x in [bound + 1, -bound - 1] {
+            assert_eq!(generator.eval(&generator.hash_small(x)), (None, false));
+        }
+    }
+
+    #[test]
+    fn residue_entropy_for_several_d() {
+        for (t, bits) in [(1, 0.0), (64, 6.0), (1000, 10f64.log2()), (6400, 6.0)] {
+            let generator = HRPPHICT::new(t, 128);
+            assert!((generator.residue_entropy_bits() - bits).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn crt_combine_small_moduli() {
+        let small = |n: u32| {
+            HRPPHICT::from_params(PublicParams {
+                t: 10,
+                d: 5,
+                s: 2,
+                a: BigUint::from(2u32),
+                n: BigUint::from(n),
+            })
+            .unwrap()
+        };
+        let (g1, g2, combined) = (small(15), small(77), small(15 * 77));
+        for x in [0, 7, -3, 123, -456] {
+            let x = BigInt::from(x);
+            let h = Hash::crt_combine(&g1.hash(&x), &g2.hash(&x)).unwrap();
+            assert_eq!(h, combined.hash(&x));
+        }
+
+        let x = BigInt::from(4);
+        let y = BigInt::from(6);
+        assert!(matches!(
+            Hash::crt_combine(&g1.hash(&x), &g2.hash(&y)),
+            Err(Error::Crt(CrtError::ResidueMismatch))
+        ));
+        assert!(matches!(
+            Hash::crt_combine(&g1.hash(&x), &small(21).hash(&x)),
+            Err(Error::Crt(CrtError::NotCoprime))
+        ));
+    }
+
+    #[test]
+    fn hash_extreme_inputs() {
+        let generator = HRPPHICT::new(1000, 256);
+        let huge: BigInt = (BigInt::from(1) << 4000u32) + 12345;
+        let extremes = [
+            BigInt::from(i128::MAX),
+            BigInt::from(i128::MIN),
+            BigInt::from(i128::MAX) + 1,
+            BigInt::from(u128::MAX),
+            -BigInt::from(u128::MAX),
+            huge.clone(),
+            -huge,
+        ];
+        for x in &extremes {
+            assert_eq!(
+                generator.hash(x) + generator.hash(&-x),
+                generator.zero_hash()
+            );
+            for m in [0, 1, -1, 999, -999, 1000, -1000] {
+                let diff = generator.hash(x) - generator.hash(&(x - m));
+                assert_eq!(generator.eval(&diff), (Some(m), true));
+            }
+        }
+    }
+
+    #[test]
+    fn is_invertible_small_modulus() {
+        let generator = HRPPHICT::from_params(PublicParams {
+            t: 10,
+            d: 5,
+            s: 2,
+            a: BigUint::from(2u32),
+            n: BigUint::from(15u32),
+        })
+        .unwrap();
+        assert!(generator.is_invertible(&BigUint::from(2u32)));
+        assert!(generator.is_invertible(&BigUint::from(14u32)));
+        assert!(!generator.is_invertible(&BigUint::from(6u32)));
+        assert!(!generator.is_invertible(&BigUint::from(0u32)));
+    }
+
+    #[test]
+    fn hash_bytes_round_trip() {
+        let mut rng = rand::thread_rng();
+        let generator = HRPPHICT::new(1000, 256);
+        for big in [false, true] {
+            for positive in [false, true] {
+                let h = generator.hash(&gen_input(&mut rng, 1000, big, positive, 256));
+                assert_eq!(Hash::from_bytes(&h.to_bytes()).unwrap(), h);
+
+                let mut buffer = Vec::new();
+                h.save(&mut buffer).unwrap();
+                assert_eq!(Hash::load(buffer.as_slice()).unwrap(), h);
+            }
+        }
+    }
+
+    #[test]
+    fn hash_base64_round_trip() {
+        let generator = HRPPHICT::new(1000, 256);
+        for x in [0, 999, -1000] {
+            let h = generator.hash_small(x);
+            let text = h.to_base64();
+            assert!(text
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+            assert_eq!(Hash::from_base64(&text).unwrap(), h);
+        }
+
+        for text in ["not base64!", "AQ=", "A"] {
+            assert!(matches!(
+                Hash::from_base64(text),
+                Err(Error::Decode(DecodeError::InvalidBase64))
+            ));
+        }
+        // valid base64 of a truncated encoding is still a decode error
+        assert!(matches!(
+            Hash::from_base64("AQ"),
+            Err(Error::Decode(DecodeError::Truncated))
+        ));
+    }
+
+    #[test]
+    fn hash_bytes_versioning() {
+        let generator = HRPPHICT::from_params(PublicParams {
+            t: 10,
+            d: 5,
+            s: 2,
+            a: BigUint::from(2u32),
+            n: BigUint::from(15u32),
+        })
+        .unwrap();
+        let v1 = [1, 0, 3, 0, 5, 0, 0, 0, 1, 8, 0, 0, 0, 1, 15];
+        let expected = generator.hash(&BigInt::from(3));
+        assert_eq!(expected.to_bytes(), v1);
+        assert_eq!(Hash::from_bytes(&v1).unwrap(), expected);
+
+        let mut tampered = v1;
+        tampered[0] = 2;
+        assert!(matches!(
+            Hash::from_bytes(&tampered),
+            Err(Error::Decode(DecodeError::UnsupportedVersion(2)))
+        ));
+        assert!(matches!(
+            Hash::from_bytes(&[]),
+            Err(Error::Decode(DecodeError::Truncated))
+        ));
+        assert!(matches!(
+            Hash::from_bytes(&v1[..v1.len() - 1]),
+            Err(Error::Decode(DecodeError::Truncated))
+        ));
+        let mut trailing = v1.to_vec();
+        trailing.push(0);
+        assert!(matches!(
+            Hash::from_bytes(&trailing),
+            Err(Error::Decode(DecodeError::TrailingBytes))
+        ));
+        let mut malformed = v1;
+        malformed[2] = 5;
+        assert!(matches!(
+            Hash::from_bytes(&malformed),
+            Err(Error::Decode(DecodeError::Malformed(_)))
+        ));
+    }
+
+    #[test]
+    fn hash_poly_eval_matches_manual() {
+        let generator = HRPPHICT::new(1000, 256);
+        let coeffs: Vec<BigInt> = [3, -2, 0, 5].iter().map(|c| BigInt::from(*c)).collect();
+        for z in [-3, 0, 1, 2, 7] {
+            // 3 - 2z + 5z^3
+            let manual = 3 - 2 * z + 5 * z * z * z;
+            assert_eq!(
+                generator.hash_poly_eval(&coeffs, &BigInt::from(z)),
+                generator.hash(&BigInt::from(manual))
+            );
+        }
+        assert_eq!(
+            generator.hash_poly_eval(&[], &BigInt::from(5)),
+            generator.zero_hash()
+        );
+    }
+
+    #[test]
+    fn params_bytes_round_trip() {
+        let generator = HRPPHICT::new(1000, 256);
+        let params = generator.params();
+        let bytes = Vec::<u8>::from(&params);
+        assert_eq!(PublicParams::try_from(bytes.as_slice()).unwrap(), params);
+    }
+
+    #[test]
+    fn params_bytes_malformed() {
+        let params = PublicParams {
+            t: 10,
+            d: 5,
+            s: 2,
+            a: BigUint::from(2u32),
+            n: BigUint::from(15u32),
+        };
+        let bytes = Vec::<u8>::from(&params);
+        assert_eq!(bytes, [1, 0, 10, 0, 5, 0, 2, 0, 0, 0, 1, 2, 0, 0, 0, 1, 15]);
+
+        assert!(matches!(
+            PublicParams::try_from(&bytes[..bytes.len() - 1]),
+            Err(Error::Decode(DecodeError::Truncated))
+        ));
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(matches!(
+            PublicParams::try_from(trailing.as_slice()),
+            Err(Error::Decode(DecodeError::TrailingBytes))
+        ));
+        let mut version = bytes.clone();
+        version[0] = 9;
+        assert!(matches!(
+            PublicParams::try_from(version.as_slice()),
+            Err(Error::Decode(DecodeError::UnsupportedVersion(9)))
+        ));
+        let mut invalid = bytes;
+        invalid[6] = 3;
+        match PublicParams::try_from(invalid.as_slice()) {
+            Err(Error::ParamInvalid(e)) => assert_eq!(
+                e.violations,
+                vec![ParamViolation::InvalidS { s: 3, expected: 2 }]
+            ),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn in_range_ct_matches_in_range() {
+        let mut rng = rand::thread_rng();
+        let t: u16 = rng.gen_range(1..3000);
+        let generator = HRPPHICT::new(t, 256);
+        let bound = i32::from(t);
+        let inputs = [
+            0,
+            bound,
+            -bound,
+            bound + 1,
+            -bound - 1,
+            rng.gen_range(-bound..=bound),
+            rng.gen_range(-3 * bound..3 * bound),
+        ];
+        for x in inputs {
+            let h = generator.hash_small(x);
+            assert_eq!(generator.in_range(&h), x.abs() <= bound);
+            assert_eq!(generator.in_range_ct(&h), generator.in_range(&h));
+        }
+        for positive in [false, true] {
+            let h = generator.hash(&gen_input(&mut rng, t, true, positive, 256));
+            assert!(!generator.in_range_ct(&h));
+        }
+    }
+
+    #[test]
+    fn rehash_migrates_in_range_values() {
+        let mut rng = rand::thread_rng();
+        let old = HRPPHICT::new(1000, 256);
+        let new = HRPPHICT::new(1000, 256);
+        for _ in 0..5 {
+            let positive = rng.gen();
+            let x = gen_input(&mut rng, 1000, false, positive, 256);
+            let migrated = new.rehash_from_eval(&old, &old.hash(&x)).unwrap();
+            assert_eq!(migrated, new.hash(&x));
+        }
+        let x = gen_input(&mut rng, 1000, true, true, 256);
+        assert_eq!(new.rehash_from_eval(&old, &old.hash(&x)), None);
+    }
+
+    #[test]
+    fn new_many_independent_generators() {
+        let generators = HRPPHICT::new_many(4, 1000, 256);
+        assert_eq!(generators.len(), 4);
+        for (i, generator) in generators.iter().enumerate() {
+            for other in &generators[i + 1..] {
+                assert_ne!(generator.n(), other.n());
+            }
+            for x in [-1000, -7, 0, 42, 1000] {
+                assert_eq!(generator.eval(&generator.hash_small(x)), (Some(x), true));
+            }
+        }
+    }
+
+    #[test]
+    fn commitment_verify() {
+        let generator = HRPPHICT::new(1000, 256);
+        let other = HRPPHICT::new(1000, 256);
+        let x = BigInt::from(-321);
+        let valid = Commitment {
+            hash: generator.hash(&x),
+            opening: x.clone(),
+        };
+        assert!(valid.verify(&generator));
+        assert!(!valid.verify(&other));
+
+        let forged = Commitment {
+            hash: generator.hash(&x),
+            opening: x + 1000,
+        };
+        assert!(!forged.verify(&generator));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn commitment_serde_round_trip() {
+        let generator = HRPPHICT::new(1000, 256);
+        let x = BigInt::from(1) << 300u32;
+        let commitment = Commitment {
+            hash: generator.hash(&x),
+            opening: x,
+        };
+        let json = serde_json::to_string(&commitment).unwrap();
+        let decoded: Commitment = serde_json::from_str(&json).unwrap();
+        assert!(decoded.verify(&generator));
+        assert_eq!(decoded, commitment);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn jwk_like_round_trip() {
+        let generator = HRPPHICT::new(1000, 256);
+        let json = generator.to_jwk_like();
+        let decoded = HRPPHICT::from_jwk_like(&json).unwrap();
+        assert_eq!(decoded.params(), generator.params());
+
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["kty"], "HRPPH-ICT");
+        assert_eq!(value["t"], 1000);
+
+        let missing = r#"{"kty":"HRPPH-ICT","n":"Dw","t":10,"d":5}"#;
+        assert!(matches!(
+            HRPPHICT::from_jwk_like(missing),
+            Err(Error::Decode(DecodeError::Json(_)))
+        ));
+        let bad_base64 = r#"{"kty":"HRPPH-ICT","n":"D=","a":"Ag","t":10,"d":5}"#;
+        assert!(matches!(
+            HRPPHICT::from_jwk_like(bad_base64),
+            Err(Error::Decode(DecodeError::InvalidBase64))
+        ));
+        let tiny = r#"{"kty":"HRPPH-ICT","n":"Dw","a":"Ag","t":10,"d":5}"#;
+        assert_eq!(
+            HRPPHICT::from_jwk_like(tiny).unwrap().n(),
+            BigUint::from(15u32)
+        );
+        let invalid = r#"{"kty":"HRPPH-ICT","n":"Dw","a":"Aw","t":10,"d":5}"#;
+        assert!(matches!(
+            HRPPHICT::from_jwk_like(invalid),
+            Err(Error::ParamInvalid(_))
+        ));
+    }
+
+    #[test]
+    fn fixed_bytes_length_matches_generator() {
+        let mut rng = rand::thread_rng();
+        for bits in [128, 256, 520] {
+            let generator = HRPPHICT::new(1000, bits);
+            let len = generator.serialized_hash_len();
+            let mut hashes = vec![generator.zero_hash(), generator.hash_small(1)];
+            for big in [false, true] {
+                for positive in [false, true] {
+                    hashes.push(generator.hash(&gen_input(&mut rng, 1000, big, positive, bits)));
+                }
+            }
+            for h in hashes {
+                let bytes = h.to_fixed_bytes();
+                assert_eq!(bytes.len(), len);
+                assert_eq!(Hash::from_fixed_bytes(&bytes).unwrap(), h);
+            }
+        }
+        assert!(matches!(
+            Hash::from_fixed_bytes(&[1, 0, 1, 0, 5, 2, 3, 15]),
+            Err(Error::Decode(DecodeError::Malformed(_)))
+        ));
+    }
+
+    #[test]
+    fn eval_unambiguous_detects_collisions() {
+        let generator = HRPPHICT::new(1000, 256);
+        assert!(matches!(
+            generator.eval_unambiguous(&generator.hash_small(-77)),
+            Ok(-77)
+        ));
+        assert!(matches!(
+            generator.eval_unambiguous(&generator.hash_small(1001)),
+            Err(Error::OutOfRange)
+        ));
+
+        // 4 has order 2 modulo 15, so every even candidate matches hash(0)
+        let broken = HRPPHICT::from_params(PublicParams {
+            t: 10,
+            d: 2,
+            s: 5,
+            a: BigUint::from(4u32),
+            n: BigUint::from(15u32),
+        })
+        .unwrap();
+        let expected: Vec<i32> = (-5..=5).rev().map(|k| 2 * k).collect();
+        assert_eq!(broken.eval_all(&broken.hash_small(0)), expected);
+        match broken.eval_unambiguous(&broken.hash_small(0)) {
+            Err(Error::Ambiguous(found)) => assert_eq!(found, expected),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn balanced_residue_around_half_d() {
+        let even = tiny_generator(12, 6, 2, 15);
+        for (x, balanced) in [(0, 0), (2, 2), (3, 3), (4, -2), (5, -1), (-1, -1), (-3, 3)] {
+            assert_eq!(even.hash_small(x).balanced_residue(), balanced);
+        }
+        let odd = tiny_generator(14, 7, 2, 15);
+        for (x, balanced) in [(3, 3), (4, -3), (6, -1), (-4, 3), (7, 0)] {
+            assert_eq!(odd.hash_small(x).balanced_residue(), balanced);
+        }
+    }
+
+    #[test]
+    fn hash_reduced_matches_hash() {
+        let mut rng = rand::thread_rng();
+        let generator = HRPPHICT::new(1000, 256);
+        for big in [false, true] {
+            let x = gen_input(&mut rng, 1000, big, true, 256);
+            assert_eq!(
+                generator.hash_reduced(&x, true),
+                (generator.hash(&x), false)
+            );
+            assert_eq!(
+                generator.hash_reduced(&x, false),
+                (generator.hash(&x), false)
+            );
+            let y = gen_input(&mut rng, 1000, big, false, 256);
+            assert_eq!(
+                generator.hash_reduced(&y, false),
+                (generator.hash(&y), true)
+            );
+        }
+    }
+
+    #[test]
+    fn sum_compact_matches_fold() {
+        let mut rng = rand::thread_rng();
+        let generator = HRPPHICT::new(1000, 256);
+        let xs: Vec<BigInt> = (0..50)
+            .map(|_| {
+                let (big, positive) = rng.gen();
+                gen_input(&mut rng, 1000, big, positive, 256)
+            })
+            .collect();
+        let compact: Vec<_> = xs.iter().map(|x| generator.hash(x).compact()).collect();
+        let folded = xs.iter().hash_fold(&generator);
+        assert_eq!(generator.expand(generator.sum_compact(&compact)), folded);
+        assert_eq!(
+            generator.expand(generator.sum_compact(&[])),
+            generator.zero_hash()
+        );
+        let h = generator.hash(&xs[0]);
+        assert_eq!(generator.expand(h.compact()), h);
+    }
+
+    #[test]
+    fn committed_test_vectors_match() {
+        let file: serde_json::Value =
+            serde_json::from_str(include_str!("test_vectors.json")).unwrap();
+        let hex = |s: &str| -> Vec<u8> {
+            (0..s.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+                .collect()
+        };
+        let seed: [u8; 32] = hex(file["seed"].as_str().unwrap()).try_into().unwrap();
+        let field = |k: &str| file[k].as_u64().unwrap() as u16;
+        let big = |k: &str| file[k].as_str().unwrap().parse::<BigUint>().unwrap();
+        let generator = HRPPHICT::from_params(PublicParams {
+            t: field("t"),
+            d: field("d"),
+            s: field("s"),
+            a: big("a"),
+            n: big("n"),
+        })
+        .unwrap();
+
+        let vectors: Vec<(BigInt, Hash)> = file["vectors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| {
+                let x = v["x"].as_str().unwrap().parse::<BigInt>().unwrap();
+                let h = Hash::from_bytes(&hex(v["hash"].as_str().unwrap())).unwrap();
+                (x, h)
+            })
+            .collect();
+        assert!(generator.verify_test_vectors(&vectors));
+        assert_eq!(HRPPHICT::test_vectors(seed, vectors.len()), vectors);
+        assert_eq!(
+            HRPPHICT::test_vector_generator(seed).params(),
+            generator.params()
+        );
+    }
+
+    #[test]
+    fn hash_diff_batch_matches_pairwise() {
+        let generator = HRPPHICT::new(100, 128);
+        let xs: Vec<BigInt> = [5, -40, 300, 0].iter().map(|&x| BigInt::from(x)).collect();
+        let ys: Vec<BigInt> = [-20, 30, 7, 100].iter().map(|&y| BigInt::from(y)).collect();
+        let a: Vec<Hash> = xs.iter().map(|x| generator.hash(x)).collect();
+        let b: Vec<Hash> = ys.iter().map(|y| generator.hash(y)).collect();
+
+        let diffs = generator.hash_diff_batch(&a, &b).unwrap();
+        for (i, diff) in diffs.iter().enumerate() {
+            assert_eq!(*diff, a[i].clone() - b[i].clone());
+        }
+        let close: Vec<bool> = diffs.iter().map(|h| generator.in_range(h)).collect();
+        assert_eq!(close, vec![true, true, false, true]);
+        assert_eq!(generator.eval(&diffs[0]), (Some(25), true));
+
+        assert!(matches!(
+            generator.hash_diff_batch(&a, &b[1..]),
+            Err(Error::ArithmeticMismatch(HashArithError::LengthMismatch))
+        ));
+        let other = HRPPHICT::new(100, 128);
+        let foreign = vec![other.hash(&BigInt::from(1)); a.len()];
+        assert!(matches!(
+            generator.hash_diff_batch(&a, &foreign),
+            Err(Error::ArithmeticMismatch(HashArithError::ParamMismatch))
+        ));
+    }
+
+    #[test]
+    fn heap_size_grows_with_modulus() {
+        for lambda in [256u64, 512, 1024] {
+            let generator = HRPPHICT::new(100, lambda);
+            let h = generator.hash(&BigInt::from(-7));
+            let modulus_bytes = (lambda / 8) as usize;
+            assert!(h.heap_size() <= 2 * modulus_bytes);
+            assert!(h.heap_size() >= modulus_bytes);
+            assert!(generator.heap_size() <= 3 * modulus_bytes);
+            assert!(generator.heap_size() >= 2 * modulus_bytes);
+        }
+    }
+
+    #[test]
+    fn heap_size_counts_order_and_factors() {
+        let safe = HRPPHICT::new_safe_prime(100, 128);
+        let public = HRPPHICT::from_params(safe.params()).unwrap();
+        assert!(safe.heap_size() >= public.heap_size() + 8 + 16);
+
+        let (escrowed, _) = HRPPHICT::new_with_escrow(100, 256);
+        let public = HRPPHICT::from_params(escrowed.params()).unwrap();
+        assert_eq!(escrowed.heap_size(), public.heap_size() + 32);
+    }
+
+    #[test]
+    fn new_safe_prime_min_lambda() {
+        let generator = HRPPHICT::new_safe_prime(10, SAFE_PRIME_MIN_LAMBDA);
+        let h = generator.hash(&BigInt::from(-3));
+        assert_eq!(generator.eval(&h), (Some(-3), true));
+    }
+
+    #[test]
+    #[should_panic(expected = "lambda must be at least")]
+    fn new_safe_prime_rejects_small_lambda() {
+        let _ = HRPPHICT::new_safe_prime(10, SAFE_PRIME_MIN_LAMBDA - 1);
+    }
+
+    #[test]
+    fn hash_salted_opens_under_salt() {
+        let generator = HRPPHICT::new(1000, 256);
+        let salt = b"session-42";
+        let x = BigInt::from(-321);
+        let y = BigInt::from(500);
+
+        let h = generator.hash_salted(&x, salt);
+        assert_ne!(h, generator.hash(&x));
+        assert_ne!(h, generator.hash_salted(&x, b"session-43"));
+        assert_eq!(generator.eval_salted(&h, salt), (Some(-321), true));
+        assert_eq!(generator.eval_salted(&h, b"session-43"), (None, false));
+
+        let sum = h + generator.hash(&y);
+        assert_eq!(sum, generator.hash_salted(&(&x + &y), salt));
+        assert_eq!(generator.eval_salted(&sum, salt), (Some(179), true));
+    }
+
+    #[test]
+    fn group_element_arithmetic() {
+        let n = BigUint::from(35u32);
+        let a = GroupElement::new(BigUint::from(38u32), n.clone());
+        assert_eq!(a.value(), &BigUint::from(3u32));
+        assert_eq!(a.modulus(), &n);
+
+        let b = GroupElement::new(BigUint::from(4u32), n.clone());
+        assert_eq!(a.mul(&b).value(), &BigUint::from(12u32));
+        assert_eq!(a.pow(&BigUint::from(3u32)).value(), &BigUint::from(27u32));
+        assert_eq!(a.pow(&BigUint::from(0u32)), GroupElement::one(n.clone()));
+
+        let a_inv = a.inverse().unwrap();
+        assert_eq!(a.mul(&a_inv), GroupElement::one(n.clone()));
+        assert!(a.is_invertible());
+
+        let shared = GroupElement::new(BigUint::from(14u32), n);
+        assert!(!shared.is_invertible());
+        assert_eq!(shared.inverse(), None);
+
+        let generator = HRPPHICT::new(100, 128);
+        let x = generator.hash(&BigInt::from(3));
+        let y = generator.hash(&BigInt::from(4));
+        let sum = x.clone() + y.clone();
+        assert_eq!(
+            sum.group_element(),
+            &x.group_element().mul(y.group_element())
+        );
+        assert_eq!(sum.group_element().modulus(), &generator.n());
+    }
+
+    #[test]
+    fn is_valid_element_detects_non_residues() {
+        let generator = HRPPHICT::new_safe_prime(100, 128);
+        assert_eq!(generator.n().bits(), 128);
+        let h = generator.hash(&BigInt::from(-42));
+        assert!(generator.is_valid_element(&h));
+        assert!(generator.is_valid_element(&(h.clone() + generator.hash(&BigInt::from(7)))));
+        assert_eq!(generator.eval(&h), (Some(-42), true));
+
+        // replace g by -g, which is not a square modulo a product of safe primes
+        let n = generator.n();
+        let width = generator.serialized_hash_len() / 2 - 2;
+        let mut bytes = h.to_fixed_bytes();
+        let g = BigUint::from_bytes_be(&bytes[5..5 + width]);
+        let flipped = (&n - g).to_bytes_be();
+        bytes[5..5 + width].fill(0);
+        bytes[5 + width - flipped.len()..5 + width].copy_from_slice(&flipped);
+        let crafted = Hash::from_fixed_bytes(&bytes).unwrap();
+        assert!(!generator.is_valid_element(&crafted));
+
+        let opaque = HRPPHICT::new(100, 128);
+        assert!(opaque.is_valid_element(&crafted));
+    }
+
+    #[test]
+    fn verify_stream_flags_wrong_openings() {
+        let generator = HRPPHICT::new(100, 128);
+        let items = vec![
+            (generator.hash(&BigInt::from(3)), BigInt::from(3)),
+            (generator.hash(&BigInt::from(-8)), BigInt::from(8)),
+            (generator.hash(&BigInt::from(1000)), BigInt::from(1000)),
+            (generator.hash(&BigInt::from(0)), BigInt::from(100)),
+        ];
+        let mut results = generator.verify_stream(items.into_iter());
+        assert_eq!(results.next(), Some(true));
+        assert_eq!(results.collect::<Vec<_>>(), vec![false, true, false]);
+    }
+
+    #[test]
+    fn from_primes_hash_and_eval() {
+        let p = BigUint::parse_bytes(b"18446744073709551557", 10).unwrap();
+        let q = BigUint::parse_bytes(b"18446744073709551533", 10).unwrap();
+        let generator = HRPPHICT::from_primes(1000, &p, &q).unwrap();
+        assert_eq!(generator.n(), &p * &q);
+        assert_eq!(generator.params().d, 10);
+
+        let h = generator.hash(&BigInt::from(-777)) + generator.hash(&BigInt::from(100));
+        assert_eq!(generator.eval(&h), (Some(-677), true));
+        assert_eq!(
+            generator.eval(&generator.hash(&BigInt::from(1001))),
+            (None, false)
+        );
+
+        let composite = BigUint::from(91u32);
+        let Err(Error::ParamInvalid(err)) = HRPPHICT::from_primes(1000, &composite, &composite)
+        else {
+            panic!("factors were accepted");
+        };
+        assert_eq!(
+            err.violations,
+            vec![
+                ParamViolation::FactorNotPrime(composite.clone()),
+                ParamViolation::FactorNotPrime(composite),
+                ParamViolation::EqualFactors,
+            ]
+        );
+    }
+
+    #[test]
+    fn hash_linear_combination_matches_combination() {
+        let generator = HRPPHICT::new(1000, 128);
+        let xs: Vec<BigInt> = [12, -250, 7].iter().map(|&x| BigInt::from(x)).collect();
+        let ws: Vec<BigInt> = [3, -2, 0].iter().map(|&w| BigInt::from(w)).collect();
+        let hs: Vec<Hash> = xs.iter().map(|x| generator.hash(x)).collect();
+
+        let combined = generator.hash_linear_combination(&ws, &hs).unwrap();
+        let plain: BigInt = ws.iter().zip(&xs).map(|(w, x)| w * x).sum();
+        assert_eq!(combined, generator.hash(&plain));
+        assert_eq!(generator.eval(&combined), (Some(536), true));
+
+        let huge: BigInt = BigInt::from(1u8) << 300;
+        let scaled = generator
+            .hash_linear_combination(std::slice::from_ref(&huge), &hs[..1])
+            .unwrap();
+        assert_eq!(scaled, generator.hash(&(huge * &xs[0])));
+
+        assert_eq!(
+            generator.hash_linear_combination(&[], &[]).unwrap(),
+            generator.zero_hash()
+        );
+        assert!(matches!(
+            generator.hash_linear_combination(&ws[..2], &hs),
+            Err(Error::ArithmeticMismatch(HashArithError::LengthMismatch))
+        ));
+    }
+
+    #[test]
+    fn describe_reports_modulus() {
+        let generator = HRPPHICT::new(1000, 256);
+        let description = generator.describe();
+        assert_eq!(description.n_bits, generator.n().bits());
+        assert_eq!(description.n, generator.n().to_string());
+        assert_eq!(
+            (description.t, description.d, description.s),
+            (1000, 10, 100)
+        );
+
+        let dump = description.to_string();
+        assert!(dump.starts_with("t = 1000\nd = 10\ns = 100\na = "));
+        assert!(dump.ends_with(&format!("n = {}\nn bits = 256", generator.n())));
+    }
+
+    #[test]
+    fn add_value_matches_hash_addition() {
+        let generator = HRPPHICT::new(100, 128);
+        let h = generator.hash(&BigInt::from(30));
+        let x = BigInt::from(-45);
+        assert_eq!(
+            generator.add_value(h.clone(), &x),
+            h.clone() + generator.hash(&x)
+        );
+        let acc = [5, 6, -7]
+            .iter()
+            .fold(h, |acc, &v| generator.add_value(acc, &BigInt::from(v)));
+        assert_eq!(generator.eval(&acc), (Some(34), true));
+    }
+
+    #[test]
+    fn randomizer_order_is_least_exponent() {
+        let (p, q) = (BigUint::from(1009u32), BigUint::from(1013u32));
+        let generator = HRPPHICT::from_primes(100, &p, &q).unwrap();
+        let params = generator.params();
+        let order = generator.randomizer_order().unwrap();
+        assert!(params.a.modpow(&order, &params.n).is_one());
+        // lcm(1008, 1012) = 2^4 * 3^2 * 7 * 11 * 23
+        for f in [2u32, 3, 7, 11, 23] {
+            if (&order % f).is_zero() {
+                assert!(!params.a.modpow(&(&order / f), &params.n).is_one());
+            }
+        }
+        assert!((BigUint::from(255024u32) % &order).is_zero());
+
+        let safe = HRPPHICT::new_safe_prime(100, 64);
+        let order = safe.randomizer_order().unwrap();
+        assert!(safe.params().a.modpow(&order, &safe.n()).is_one());
+
+        assert_eq!(HRPPHICT::new(100, 128).randomizer_order(), None);
+    }
+
+    #[test]
+    fn complex_hash_componentwise() {
+        let generator = HRPPHICT::new(100, 128);
+        let z = generator.hash_complex(&BigInt::from(3), &BigInt::from(-4));
+        let w = generator.hash_complex(&BigInt::from(-10), &BigInt::from(25));
+
+        let sum = z.clone() + w.clone();
+        assert_eq!(
+            sum,
+            generator.hash_complex(&BigInt::from(-7), &BigInt::from(21))
+        );
+        assert_eq!(generator.eval_complex(&sum), Some((-7, 21)));
+        assert_eq!(generator.eval_complex(&(z - w.clone())), Some((13, -29)));
+
+        let parts = ComplexHash {
+            re: generator.hash(&BigInt::from(3)),
+            im: generator.hash(&BigInt::from(-4)),
+        };
+        assert_eq!(generator.eval_complex(&parts), Some((3, -4)));
+
+        let far = generator.hash_complex(&BigInt::from(5), &BigInt::from(101));
+        assert_eq!(generator.eval_complex(&far), None);
+        assert_eq!(generator.eval_complex(&(far - w)), Some((15, 76)));
+    }
+
+    #[test]
+    fn hash_deltas_open_to_steps() {
+        let generator = HRPPHICT::new(100, 128);
+        let series = [-50, -20, 0, 45, 145, 246];
+        let hs: Vec<Hash> = series
+            .iter()
+            .map(|&x| generator.hash(&BigInt::from(x)))
+            .collect();
+
+        let deltas = generator.hash_deltas(&hs).unwrap();
+        assert_eq!(deltas.len(), series.len() - 1);
+        let steps: Vec<Option<i32>> = deltas.iter().map(|h| generator.eval(h).0).collect();
+        assert_eq!(steps, vec![Some(30), Some(20), Some(45), Some(100), None]);
+
+        assert!(generator.hash_deltas(&hs[..1]).unwrap().is_empty());
+        let other = HRPPHICT::new(100, 128);
+        assert!(matches!(
+            generator.hash_deltas(&[hs[0].clone(), other.zero_hash()]),
+            Err(Error::ArithmeticMismatch(HashArithError::ParamMismatch))
+        ));
+    }
+
+    #[test]
+    fn check_additive_across_inputs() {
+        let mut rng = rand::thread_rng();
+        let t = 1000;
+        let lambda = 256;
+        let generator = HRPPHICT::new(t, lambda);
+        let categories = [(false, true), (false, false), (true, true), (true, false)];
+        for &(big_a, positive_a) in &categories {
+            for &(big_b, positive_b) in &categories {
+                let a = gen_input(&mut rng, t, big_a, positive_a, lambda);
+                let b = gen_input(&mut rng, t, big_b, positive_b, lambda);
+                assert!(generator.check_additive(&a, &b));
+            }
+        }
+    }
+
+    #[test]
+    fn hash_array_matches_hash() {
+        let generator = HRPPHICT::new(100, 128);
+        let xs: [BigInt; 16] = std::array::from_fn(|i| BigInt::from(i as i32 * 37 - 300));
+        let hs = generator.hash_array(&xs);
+        for (x, h) in xs.iter().zip(&hs) {
+            assert_eq!(*h, generator.hash(x));
+        }
+        assert_eq!(generator.hash_array::<0>(&[]).len(), 0);
+    }
+
+    #[test]
+    fn detect_parameter_collisions_finds_clones() {
+        let first = HRPPHICT::new(100, 128);
+        let second = HRPPHICT::new(100, 128);
+        let gens = vec![
+            first.clone(),
+            second.clone(),
+            first.clone(),
+            HRPPHICT::new(100, 128),
+            first,
+            second,
+        ];
+        assert_eq!(
+            detect_parameter_collisions(&gens),
+            vec![(0, 2), (0, 4), (1, 5), (2, 4)]
+        );
+        assert!(detect_parameter_collisions(&gens[..2]).is_empty());
+    }
+
+    #[test]
+    fn small_modulus_end_to_end() {
+        for lambda in [256u64, 48, 24] {
+            let generator = HRPPHICT::new(100, lambda);
+            assert_eq!(generator.n().bits(), lambda);
+            let h = generator.hash(&BigInt::from(-60)) + generator.hash(&BigInt::from(15));
+            assert_eq!(generator.eval(&h).0, Some(-45));
+            assert!(generator.check_additive(&(BigInt::from(1) << 100), &BigInt::from(-3)));
+        }
+    }
+
+    #[test]
+    fn hidden_bits_subtract_leakage() {
+        let generator = tiny_generator(1000, 10, 2, 35);
+        let leak = 10f64.log2();
+        assert!((generator.hidden_bits(64) - (64.0 - leak)).abs() < 1e-9);
+        assert!((generator.hidden_bits(4) - (4.0 - leak)).abs() < 1e-9);
+        assert_eq!(generator.hidden_bits(3), 0.0);
+        assert_eq!(generator.hidden_bits(0), 0.0);
+
+        let power_d = tiny_generator(64, 64, 2, 35);
+        assert_eq!(power_d.hidden_bits(32), 26.0);
+    }
+
+    #[test]
+    fn hash_commitment_opens_to_inner_hash() {
+        let inner_gen = HRPPHICT::new(100, 128);
+        let outer_gen = HRPPHICT::new(100, 256);
+        let inner = inner_gen.hash(&BigInt::from(-17));
+        let outer = outer_gen.hash_commitment(&inner);
+
+        let opening = BigInt::from_bytes_be(num_bigint::Sign::Plus, &inner.to_bytes());
+        let commitment = Commitment {
+            hash: outer,
+            opening,
+        };
+        assert!(commitment.verify(&outer_gen));
+
+        let (_, bytes) = commitment.opening.to_bytes_be();
+        let reopened = Hash::from_bytes(&bytes).unwrap();
+        assert_eq!(reopened, inner);
+        assert_eq!(inner_gen.eval(&reopened), (Some(-17), true));
+    }
+
+    #[test]
+    fn recommend_d_meets_round_budget() {
+        assert_eq!(HRPPHICT::recommend_d(10000, 201), 100);
+        assert_eq!(HRPPHICT::recommend_d(10000, 200), 101);
+        assert_eq!(HRPPHICT::recommend_d(100, 201), 1);
+        assert_eq!(HRPPHICT::recommend_d(100, 21), 10);
+        assert_eq!(HRPPHICT::recommend_d(100, 4), 51);
+        assert_eq!(HRPPHICT::recommend_d(100, 3), 67);
+        assert_eq!(HRPPHICT::recommend_d(100, 2), 100);
+        assert_eq!(HRPPHICT::recommend_d(1, 0), 1);
+    }
+
+    #[test]
+    fn recommend_d_exact_bound() {
+        let base = HRPPHICT::new(100, 128);
+        let worst = |t: u16, d: u16| {
+            let mut params = base.params();
+            params.t = t;
+            params.d = d;
+            params.s = t / d;
+            let generator = HRPPHICT::from_params(params).unwrap();
+            (0..d)
+                .map(|r| {
+                    generator
+                        .eval_candidates(&generator.hash_small(r.into()))
+                        .len()
+                })
+                .max()
+                .unwrap()
+        };
+        for (t, max_rounds) in [(100u16, 3usize), (100, 4), (100, 7), (50, 7), (1000, 25)] {
+            let d = HRPPHICT::recommend_d(t, max_rounds);
+            assert!(worst(t, d) <= max_rounds);
+            assert!(worst(t, d - 1) > max_rounds);
+        }
+    }
+
+    #[test]
+    fn diff_eval_close_differences() {
+        let generator = HRPPHICT::new(100, 128);
+        let a = generator.hash(&BigInt::from(1234));
+        let b = generator.hash(&BigInt::from(1300));
+        let c = generator.hash(&BigInt::from(1500));
+        assert_eq!(generator.diff_eval(&a, &b), (Some(-66), true));
+        assert_eq!(generator.diff_eval(&b, &a), (Some(66), true));
+        assert_eq!(generator.diff_eval(&a, &a), (Some(0), true));
+        assert_eq!(generator.diff_eval(&c, &a), (None, false));
+
+        let other = HRPPHICT::new(100, 128);
+        let foreign = other.hash(&BigInt::from(1234));
+        assert_eq!(generator.diff_eval(&a, &foreign), (None, false));
+    }
+
+    #[test]
+    fn batch_verify_openings_rejects_bad_opening() {
+        let mut rng = rand::thread_rng();
+        let generator = HRPPHICT::new(100, 256);
+        let openings: Vec<BigInt> = (0..8)
+            .map(|i| gen_input(&mut rng, 100, i % 2 == 0, i % 3 == 0, 256))
+            .collect();
+        let hashes: Vec<Hash> = openings.iter().map(|o| generator.hash(o)).collect();
+        assert!(generator.batch_verify_openings(&hashes, &openings));
+        assert!(generator.batch_verify_openings(&[], &[]));
+
+        // same residue, wrong value
+        let mut wrong = openings.clone();
+        wrong[5] += 1000;
+        assert!(!generator.batch_verify_openings(&hashes, &wrong));
+
+        let mut wrong_residue = openings.clone();
+        wrong_residue[2] += 1;
+        assert!(!generator.batch_verify_openings(&hashes, &wrong_residue));
+        assert!(!generator.batch_verify_openings(&hashes[1..], &openings));
+    }
+
+    #[test]
+    fn pow_a_matches_hash() {
+        let mut rng = rand::thread_rng();
+        let generator = HRPPHICT::new(100, 256);
+        for (big, positive) in [(false, true), (false, false), (true, true), (true, false)] {
+            let x = gen_input(&mut rng, 100, big, positive, 256);
+            assert_eq!(
+                &generator.pow_a(&x),
+                generator.hash(&x).group_element().value()
+            );
+        }
+        assert!(generator.pow_a(&BigInt::from(0)).is_one());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn params_serde_decimal_round_trip() {
+        let params = HRPPHICT::new(1000, 128).params();
+        let json = serde_json::to_string(&params).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["a"], serde_json::json!(params.a.to_string()));
+        assert_eq!(value["n"], serde_json::json!(params.n.to_string()));
+        assert_eq!(value["t"], serde_json::json!(1000));
+
+        let decoded: PublicParams = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, params);
+        let malformed = json.replace(&params.n.to_string(), "0x1f");
+        assert!(serde_json::from_str::<PublicParams>(&malformed).is_err());
+    }
+
+    #[test]
+    fn new_with_rounds_bounds_scan() {
+        for (t, max_rounds) in [(1000u16, 25usize), (1000, 200), (50, 7), (100, 3)] {
+            let generator = HRPPHICT::new_with_rounds(t, 128, max_rounds);
+            let d = generator.params().d;
+            assert_eq!(d, HRPPHICT::recommend_d(t, max_rounds));
+            let worst = (0..i32::from(d))
+                .map(|r| generator.eval_candidates(&generator.hash_small(r)).len())
+                .max()
+                .unwrap();
+            assert!(worst <= max_rounds);
+            let x = i32::from(t) - 1;
+            assert_eq!(generator.eval(&generator.hash_small(-x)), (Some(-x), true));
+        }
+
+        // Below the RSA minimum the small-modulus fallback of new is used
+        let generator = HRPPHICT::new_with_rounds(100, 32, 21);
+        assert_eq!(generator.params().d, 10);
+        assert_eq!(
+            generator.eval(&generator.hash_small(-97)),
+            (Some(-97), true)
+        );
+    }
+
+    #[test]
+    fn matches_any_finds_members() {
+        let generator = HRPPHICT::new(100, 256);
+        let huge: BigInt = BigInt::from(1) << 300;
+        let allowed = vec![BigInt::from(7), -huge.clone(), BigInt::from(-2000)];
+        for x in &allowed {
+            assert!(generator.matches_any(&generator.hash(x), &allowed));
+        }
+        assert!(!generator.matches_any(&generator.hash(&huge), &allowed));
+        assert!(!generator.matches_any(&generator.hash(&BigInt::from(8)), &allowed));
+        assert!(!generator.matches_any(&generator.hash(&BigInt::from(7)), &[]));
+    }
+
+    #[test]
+    fn hash_mean_opens_to_mean() {
+        let (p, q) = (BigUint::from(1019u32), BigUint::from(1031u32));
+        let generator = HRPPHICT::from_primes(100, &p, &q).unwrap();
+        let hs: Vec<Hash> = [10, -20, 73]
+            .iter()
+            .map(|&x| generator.hash(&BigInt::from(x)))
+            .collect();
+        let mean = generator.hash_mean(&hs).unwrap();
+        assert_eq!(mean, generator.hash(&BigInt::from(21)));
+        assert_eq!(generator.eval(&mean), (Some(21), true));
+        assert_eq!(generator.hash_mean(&[]), None);
+
+        let safe = HRPPHICT::new_safe_prime(100, 64);
+        let hs: Vec<Hash> = [-7, -9, -14]
+            .iter()
+            .map(|&x| safe.hash(&BigInt::from(x)))
+            .collect();
+        assert_eq!(safe.hash_mean(&hs), Some(safe.hash(&BigInt::from(-10))));
+        // 2 shares a factor with d = 100
+        assert_eq!(safe.hash_mean(&hs[..2]), None);
+
+        let opaque = HRPPHICT::new(100, 128);
+        assert_eq!(opaque.hash_mean(&[opaque.hash(&BigInt::from(4))]), None);
+    }
+
+    #[test]
+    fn hash_mean_with_escrowed_key() {
+        let (generator, _) = HRPPHICT::new_with_escrow(100, 1024);
+        // k = 65537 is the RSA public exponent, so it is coprime to
+        // lcm(p-1, q-1); the inputs sum to 65537 * 21
+        let mut hs = vec![generator.hash(&BigInt::from(20)); 65536];
+        hs.push(generator.hash(&BigInt::from(20 + 65537)));
+        let mean = generator.hash_mean(&hs).unwrap();
+        assert_eq!(mean, generator.hash(&BigInt::from(21)));
+        assert_eq!(generator.eval(&mean), (Some(21), true));
+    }
+
+    #[test]
+    fn error_variants_match_failures() {
+        let generator = tiny_generator(10, 2, 2, 15);
+        let h = generator.hash_small(3);
+        let real = HRPPHICT::new(10, 128);
+        // g = 3 shares a factor with n = 15
+        let crafted = Hash::from_fixed_bytes(&[1, 0, 0, 0, 2, 3, 15]).unwrap();
+        assert!(matches!(
+            generator.hash_diff_batch(std::slice::from_ref(&h), std::slice::from_ref(&crafted)),
+            Err(Error::InverseFailed)
+        ));
+        assert!(matches!(
+            generator.hash_linear_combination(&[BigInt::from(-1)], &[crafted]),
+            Err(Error::InverseFailed)
+        ));
+        assert!(matches!(
+            generator.hash_deltas(&[h.clone(), real.zero_hash()]),
+            Err(Error::ArithmeticMismatch(HashArithError::ParamMismatch))
+        ));
+        assert!(matches!(
+            real.eval_unambiguous(&real.hash_small(11)),
+            Err(Error::OutOfRange)
+        ));
+        assert!(matches!(
+            Hash::from_bytes(&[7]),
+            Err(Error::Decode(DecodeError::UnsupportedVersion(7)))
+        ));
+        assert!(matches!(
+            HRPPHICT::from_primes(10, &BigUint::from(4u32), &BigUint::from(7u32)),
+            Err(Error::ParamInvalid(_))
+        ));
+
+        let err = Error::from(DecodeError::Truncated);
+        assert_eq!(err.to_string(), "input is truncated");
+        assert_eq!(
+            Error::InverseFailed.to_string(),
+            "group element is not invertible modulo n"
+        );
+    }
+
+    #[test]
+    fn small_value_cache_matches_hash() {
+        let generator = HRPPHICT::new(1000, 128);
+        let cache = generator.build_small_value_cache(300);
+        assert_eq!(cache.max_value(), 300);
+        for v in [0u16, 1, 9, 10, 123, 300] {
+            assert_eq!(
+                cache.hash(&generator, v),
+                Some(generator.hash(&BigInt::from(v)))
+            );
+        }
+        assert_eq!(cache.hash(&generator, 301), None);
+        assert_eq!(cache.hash(&HRPPHICT::new(1000, 128), 5), None);
+    }
+
+    #[test]
+    fn split_join_round_trip() {
+        let generator = HRPPHICT::new(100, 128);
+        let h = generator.hash(&BigInt::from(-73));
+        let (public, secret) = h.clone().split();
+        assert_eq!((public.r(), public.d()), (27, 100));
+        assert_eq!(secret.element(), h.group_element());
+        assert_eq!(format!("{:?}", secret), "SecretGroupElement(..)");
+        assert_eq!(Hash::join(public, secret), h);
+    }
+
+    #[test]
+    fn huge_negative_residue_is_least_non_negative() {
+        let generator = HRPPHICT::new(10000, 256);
+        let d = BigInt::from(100);
+        let power: BigInt = BigInt::from(1) << 2000;
+        let inputs: [BigInt; 5] = [
+            -(&power + BigInt::from(3)),
+            -&power,
+            -(&power * BigInt::from(100)),
+            -(&power + BigInt::from(99)),
+            -(&power * &power * &power) - BigInt::from(1),
+        ];
+        for x in inputs {
+            let h = generator.hash(&x);
+            let expected = ((&x % &d) + &d) % &d;
+            let (public, _) = h.clone().split();
+            assert_eq!(BigInt::from(public.r()), expected);
+            assert!(public.r() < public.d());
+            // the residue stays consistent with the group element
+            let shifted = h + generator.hash(&(BigInt::from(42) - &x));
+            assert_eq!(generator.eval(&shifted), (Some(42), true));
+        }
+    }
+
+    #[test]
+    fn eval_transcript_is_deterministic() {
+        let generator = HRPPHICT::new(1000, 256);
+        let width = generator.n().bits().div_ceil(8) as usize;
+        let h = generator.hash_small(-37);
+        let transcript = generator.eval_transcript(&h);
+        assert_eq!(transcript, generator.eval_transcript(&h.clone()));
+
+        // one record per candidate up to and including the match
+        let scanned = generator.eval_candidates(&h);
+        let position = scanned.iter().position(|c| *c == -37).unwrap();
+        assert_eq!(transcript.len(), (position + 1) * (4 + width));
+        let last = &transcript[position * (4 + width)..];
+        assert_eq!(&last[..4], &(-37i32).to_be_bytes());
+
+        let other = generator.eval_transcript(&generator.hash_small(-36));
+        assert_ne!(transcript, other);
+    }
+
+    #[test]
+    fn equals_value_checks_one_constant() {
+        let generator = HRPPHICT::new(1000, 256);
+        let value: BigInt = BigInt::from(1) << 300;
+        let h = generator.hash(&value);
+        assert!(generator.equals_value(&h, &value));
+        assert!(!generator.equals_value(&h, &(&value + BigInt::from(1))));
+        // same residue, different group element
+        assert!(!generator.equals_value(&h, &(&value + BigInt::from(100))));
+
+        let other = HRPPHICT::new(1000, 256);
+        assert!(!generator.equals_value(&other.hash(&value), &value));
+    }
+
+    #[test]
+    fn range_hashes_round_trip_through_eval() {
+        let generator = HRPPHICT::new(20, 256);
+        let mut count = 0;
+        for (x, h) in generator.range_hashes() {
+            assert_eq!(h, generator.hash(&BigInt::from(x)));
+            assert_eq!(generator.eval(&h), (Some(x), true));
+            count += 1;
+        }
+        assert_eq!(count, 41);
+    }
+
+    #[test]
+    #[should_panic]
+    fn range_hashes_rejects_large_threshold() {
+        let generator = HRPPHICT::new(RANGE_HASHES_MAX_THRESHOLD + 1, 256);
+        let _ = generator.range_hashes();
+    }
+
+    #[test]
+    fn centered_generator_recovers_values_around_center() {
+        let center = BigInt::from(1_000_000);
+        let generator = HRPPHICT::new_centered(center.clone(), 500, 256);
+        for offset in [-500, -1, 0, 1, 499, 500] {
+            let x = &center + BigInt::from(offset);
+            let h = generator.hash(&x);
+            assert_eq!(h, generator.hash_small(1_000_000 + offset));
+            assert_eq!(generator.eval(&h), (Some(1_000_000 + offset), true));
+            assert_eq!(generator.eval_bigint(&h), (Some(x), true));
+        }
+        assert!(!generator.in_range(&generator.hash(&(&center + BigInt::from(501)))));
+        assert!(!generator.in_range(&generator.hash(&BigInt::zero())));
+
+        // the center cancels in differences
+        let a = generator.hash(&(&center + BigInt::from(120)));
+        let b = generator.hash(&(&center - BigInt::from(80)));
+        assert_eq!(generator.diff_eval(&a, &b), (Some(200), true));
+        let shifted = generator.add_value(b, &BigInt::from(30));
+        assert_eq!(generator.eval(&shifted), (Some(1_000_000 - 50), true));
+
+        // center + t beyond i32 only opens as a BigInt
+        let far: BigInt = BigInt::from(1) << 40;
+        let generator = HRPPHICT::new_centered(far.clone(), 10, 256);
+        let h = generator.hash(&(&far + BigInt::from(7)));
+        assert_eq!(generator.eval(&h), (None, false));
+        assert_eq!(
+            generator.eval_bigint(&h),
+            (Some(&far + BigInt::from(7)), true)
+        );
+    }
+
+    #[test]
+    fn centered_generator_hash_reduced_negative_input() {
+        let generator = HRPPHICT::new_centered(BigInt::from(-5000), 100, 256);
+        let x = BigInt::from(-4990);
+        let (h, negative) = generator.hash_reduced(&x, true);
+        assert!(!negative);
+        assert_eq!(h, generator.hash(&x));
+        assert_eq!(generator.eval(&h), (Some(-4990), true));
+    }
+
+    #[test]
+    fn centered_generator_readers_return_values() {
+        let center = BigInt::from(5000);
+        let generator = HRPPHICT::new_centered(center.clone(), 100, 256);
+        let h = generator.hash_small(4930);
+
+        assert_eq!(generator.eval_all(&h), vec![4930]);
+        assert_eq!(generator.eval_unambiguous(&h).unwrap(), 4930);
+        assert!(generator.eval_candidates(&h).contains(&4930));
+        assert!(generator
+            .eval_candidates(&h)
+            .iter()
+            .all(|x| (4900..=5100).contains(x)));
+        let r = generator.bucket(&h);
+        assert_eq!(
+            generator.congruent_candidates(r),
+            generator
+                .range_hashes()
+                .filter(|(_, g)| generator.bucket(g) == r)
+                .map(|(x, _)| x)
+                .collect::<Vec<_>>()
+        );
+        for (x, g) in generator.range_hashes() {
+            assert!((4900..=5100).contains(&x));
+            assert_eq!(generator.eval(&g), (Some(x), true));
+        }
+
+        // hash_reduced and hash_mod hash the offset from the center
+        assert_eq!(generator.hash_reduced(&BigInt::from(4930), false).0, h);
+        assert_eq!(
+            generator.hash_reduced(&BigInt::from(5030), true).0,
+            generator.hash_small(5030)
+        );
+        let modulus = BigInt::from(201);
+        let h = generator.hash_mod(&BigInt::from(4930 + 201 * 7), &modulus);
+        assert_eq!(generator.eval(&h), (Some(4930), true));
+        let h = generator.hash_mod(&BigInt::from(-2), &modulus);
+        assert_eq!(generator.eval(&h), (Some(5023), true));
+
+        // the exponent found is the offset, the result the preimage
+        let h = generator.hash(&(&center + BigInt::from(77)));
+        assert_eq!(
+            generator.recover_exponent_mod_order(&h),
+            Some(BigUint::from(5077u32))
+        );
+    }
+
+    #[test]
+    fn centered_generator_complex_round_trip() {
+        let generator = HRPPHICT::new_centered(BigInt::from(-300), 100, 256);
+        let z = generator.hash_complex(&BigInt::from(-250), &BigInt::from(-399));
+        assert_eq!(generator.eval_complex(&z), Some((-250, -399)));
+        let far = generator.hash_complex(&BigInt::from(-250), &BigInt::from(0));
+        assert_eq!(generator.eval_complex(&far), None);
+    }
+
+    #[test]
+    fn centered_generator_batch_verify_openings() {
+        let generator = HRPPHICT::new_centered(BigInt::from(12_345), 100, 256);
+        let openings: Vec<BigInt> = [12_300, 12_345, 12_444, -7, 1 << 20]
+            .into_iter()
+            .map(BigInt::from)
+            .collect();
+        let hashes: Vec<Hash> = openings.iter().map(|o| generator.hash(o)).collect();
+        assert!(generator.batch_verify_openings(&hashes, &openings));
+
+        let mut wrong = openings.clone();
+        wrong[1] += 100;
+        assert!(!generator.batch_verify_openings(&hashes, &wrong));
+        let mut wrong_residue = openings;
+        wrong_residue[3] += 1;
+        assert!(!generator.batch_verify_openings(&hashes, &wrong_residue));
+    }
+
+    #[test]
+    fn centered_generator_hash_sum_matches_fold() {
+        let generator = HRPPHICT::new_centered(BigInt::from(5000), 100, 256);
+        let xs: Vec<BigInt> = [4990, 5010, 5020, -3]
+            .into_iter()
+            .map(BigInt::from)
+            .collect();
+        let folded = xs
+            .iter()
+            .map(|x| generator.hash(x))
+            .reduce(|acc, h| acc + h)
+            .unwrap();
+        assert_eq!(generator.hash_sum(&xs), folded);
+        assert_eq!(generator.hash_sum(&xs[..1]), generator.hash(&xs[0]));
+        assert_eq!(generator.hash_sum(&[]), generator.zero_hash());
+    }
+
+    #[test]
+    fn centered_generator_hash_salted() {
+        let generator = HRPPHICT::new_centered(BigInt::from(5000), 100, 256);
+        let (x, y) = (BigInt::from(4990), BigInt::from(5030));
+        let salted = generator.hash_salted(&x, b"context");
+        assert_eq!(
+            generator.eval_salted(&salted, b"context"),
+            (Some(4990), true)
+        );
+        assert_eq!(
+            salted + generator.hash(&y),
+            generator.hash_salted(&(&x + &y - 5000), b"context")
+        );
+    }
+
+    #[test]
+    fn centered_generator_hash_linear_combination() {
+        let generator = HRPPHICT::new_centered(BigInt::from(5000), 100, 256);
+        let xs = [4990, 5010, 5003].map(BigInt::from);
+        let weights = [2, -1, 3].map(BigInt::from);
+        let hs: Vec<Hash> = xs.iter().map(|x| generator.hash(x)).collect();
+        let combination: BigInt = weights.iter().zip(&xs).map(|(w, x)| w * x).sum();
+        assert_eq!(
+            generator.hash_linear_combination(&weights, &hs).unwrap(),
+            generator.hash(&combination)
+        );
+        assert_eq!(
+            generator.hash_linear_combination(&[], &[]).unwrap(),
+            generator.hash(&BigInt::from(0))
+        );
+    }
+
+    #[test]
+    fn has_residue_filters_before_eval() {
+        let generator = HRPPHICT::new(1000, 256);
+        let expected = 437;
+        let residue = (expected % 10) as u16;
+        let hashes: Vec<Hash> = (430..440).map(|x| generator.hash_small(x)).collect();
+        let opened: Vec<i32> = hashes
+            .iter()
+            .filter(|h| generator.has_residue(h, residue))
+            .filter_map(|h| generator.eval(h).0)
+            .collect();
+        assert_eq!(opened, vec![expected]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn has_residue_rejects_residue_above_d() {
+        let generator = HRPPHICT::new(1000, 256);
+        generator.has_residue(&generator.zero_hash(), 10);
+    }
+
+    #[test]
+    fn prime_d_generator_hashes_and_evals() {
+        for (t, expected_d) in [(2, 2), (100, 97), (150, 2), (10000, 97), (65535, 653)] {
+            let generator = HRPPHICT::new_prime_d(t, 256);
+            let params = generator.params();
+            assert_eq!(params.d, expected_d);
+            assert!((2..params.d).all(|f| !params.d.is_multiple_of(f)));
+            assert_eq!(params.s, t / params.d);
+            let bound = i32::from(t);
+            for x in [0, 1, -1, bound, -bound] {
+                assert_eq!(generator.eval(&generator.hash_small(x)), (Some(x), true));
+            }
+        }
+
+        // Below the RSA minimum the small-modulus fallback of new is used
+        let generator = HRPPHICT::new_prime_d(100, 32);
+        assert_eq!(generator.params().d, 97);
+        assert_eq!(
+            generator.eval(&generator.hash_small(-98)),
+            (Some(-98), true)
+        );
+    }
+
+    #[test]
+    fn pow_element_matches_modpow() {
+        let generator = HRPPHICT::new(1000, 256);
+        let n = generator.n();
+        let h = generator.hash_small(123);
+        let g = h.group_element().value().clone();
+        let e: BigInt = (BigInt::from(1) << 200) + BigInt::from(17);
+        assert_eq!(h.pow_element(&e), g.modpow(e.magnitude(), &n));
+        assert_eq!(h.pow_element(&BigInt::zero()), BigUint::one());
+        // g^-e is the inverse of g^e
+        let inverse = h.pow_element(&-&e);
+        assert_eq!((inverse * h.pow_element(&e)) % &n, BigUint::one());
+        // g^e is a^(123 e)
+        assert_eq!(
+            h.pow_element(&BigInt::from(5)),
+            generator.pow_a(&BigInt::from(615))
+        );
+    }
+
+    #[test]
+    fn hash_timestamp_uses_epoch_seconds() {
+        let generator = HRPPHICT::new(1000, 256);
+        let ts = UNIX_EPOCH + Duration::from_millis(1_700_000_000_999);
+        assert_eq!(
+            generator.hash_timestamp(ts).unwrap(),
+            generator.hash(&BigInt::from(1_700_000_000u64))
+        );
+        assert_eq!(
+            generator.hash_duration(Duration::from_secs(90)),
+            generator.hash(&BigInt::from(90))
+        );
+        // timestamps a minute apart differ by 60 under the homomorphism
+        let later = generator
+            .hash_timestamp(ts + Duration::from_secs(60))
+            .unwrap();
+        let earlier = generator.hash_timestamp(ts).unwrap();
+        assert_eq!(generator.diff_eval(&later, &earlier), (Some(60), true));
+
+        assert!(matches!(
+            generator.hash_timestamp(UNIX_EPOCH - Duration::from_secs(1)),
+            Err(Error::BeforeEpoch)
+        ));
+    }
+
+    #[test]
+    fn verifier_context_combines_and_compares() {
+        let generator = HRPPHICT::new(1000, 256);
+        let verifier = VerifierContext::from_params(&generator.params());
+        assert_eq!(
+            verifier,
+            VerifierContext::new(generator.params().d, generator.n())
+        );
+        let a = generator.hash_small(300);
+        let b = generator.hash_small(-120);
+        let sum = verifier.add(&a, &b).unwrap();
+        assert!(verifier.compare(&sum, &generator.hash_small(180)));
+        assert!(!verifier.compare(&sum, &generator.hash_small(181)));
+        let diff = verifier.sub(&a, &b).unwrap();
+        assert!(verifier.compare(&diff, &generator.hash_small(420)));
+
+        let other = HRPPHICT::new(1000, 256).hash_small(300);
+        assert!(matches!(
+            verifier.add(&a, &other),
+            Err(Error::ArithmeticMismatch(HashArithError::ParamMismatch))
+        ));
+        assert!(!verifier.compare(&other, &other));
+    }
+
+    #[test]
+    fn prove_leq_accepts_ordered_pairs_within_threshold() {
+        let generator = HRPPHICT::new(1000, 256);
+        let hash = |x: i64| generator.hash(&BigInt::from(x));
+        let base = 5_000_000_000i64;
+        assert!(generator.prove_leq(&hash(base), &hash(base + 1)));
+        assert!(generator.prove_leq(&hash(base), &hash(base + 1000)));
+        assert!(generator.prove_leq(&hash(base), &hash(base)));
+        assert!(!generator.prove_leq(&hash(base + 1), &hash(base)));
+        assert!(!generator.prove_leq(&hash(base), &hash(base + 1001)));
+        assert!(!generator.prove_leq(&hash(base + 5000), &hash(base)));
+    }
+
+    #[test]
+    fn bucket_counts_tally_residues() {
+        let generator = HRPPHICT::new(1000, 256);
+        let mut hs: Vec<Hash> = [3, 13, -7, 5, 1_000_000_005]
+            .iter()
+            .map(|x| generator.hash(&BigInt::from(*x)))
+            .collect();
+        assert_eq!(generator.bucket(&hs[2]), 3);
+        hs.push(HRPPHICT::new(1000, 256).hash_small(3));
+
+        let counts = generator.bucket_counts(&hs);
+        assert_eq!(counts.len(), 10);
+        assert_eq!(counts[3], 3);
+        assert_eq!(counts[5], 2);
+        assert_eq!(counts.iter().sum::<usize>(), 5);
+    }
+
+    #[test]
+    fn exact_bits_generator_has_requested_length() {
+        for bits in [20, 33, 256, 513] {
+            let generator = HRPPHICT::new_exact_bits(1000, bits).unwrap();
+            assert_eq!(generator.n().bits(), bits);
+            assert_eq!(
+                generator.eval(&generator.hash_small(-999)),
+                (Some(-999), true)
+            );
+        }
+    }
+
+    #[test]
+    fn pair_generator_recovers_small_pairs() {
+        let mut rng = rand::thread_rng();
+        let first = HRPPHICT::new(50, 256);
+        let mut params = first.params();
+        params.t = 300;
+        params.d = 3;
+        params.s = 100;
+        params.a = rng.gen_biguint_below(&params.n);
+        let second = HRPPHICT::from_params(params).unwrap();
+        let pair = first.pair_with(&second).unwrap();
+
+        for (x, y) in [(0, 0), (50, -300), (-50, 300), (7, -11)] {
+            let h = pair.hash(&BigInt::from(x), &BigInt::from(y));
+            assert_eq!(pair.eval(&h), Some((x, y)));
+        }
+        let h = pair.hash(&BigInt::from(51), &BigInt::from(0));
+        assert_eq!(pair.eval(&h), None);
+
+        let unrelated = HRPPHICT::new(50, 256);
+        assert!(matches!(
+            first.pair_with(&unrelated),
+            Err(Error::ParamInvalid(e)) if e.violations == vec![ParamViolation::ModulusMismatch]
+        ));
+    }
+
+    #[test]
+    fn collision_probability_is_sane() {
+        let generator = HRPPHICT::new(1000, 256);
+        let p = generator.collision_probability();
+        assert!(p > 0.0 && p < 1e-60);
+
+        // more values in range means more pairs
+        let wider = HRPPHICT::from_params(PublicParams {
+            t: 60000,
+            d: 600,
+            s: 100,
+            ..generator.params()
+        })
+        .unwrap();
+        assert!(wider.collision_probability() > p);
+
+        assert_eq!(tiny_generator(10, 5, 2, 15).collision_probability(), 1.0);
+        assert_eq!(HRPPHICT::new(10, 2048).collision_probability(), 0.0);
+    }
+
+    #[test]
+    fn hash_mod_recovers_balanced_representative() {
+        let generator = HRPPHICT::new(1000, 256);
+        let modulus = BigInt::from(2001);
+        for (x, expected) in [(0, 0), (1000, 1000), (1001, -1000), (2000, -1), (-1, -1)] {
+            let h = generator.hash_mod(&BigInt::from(x), &modulus);
+            assert_eq!(generator.eval(&h), (Some(expected), true));
+        }
+        let big: BigInt = (BigInt::from(1) << 300) * &modulus + BigInt::from(1500);
+        let h = generator.hash_mod(&big, &modulus);
+        assert_eq!(generator.eval(&h), (Some(-501), true));
+
+        let even = BigInt::from(10);
+        assert_eq!(
+            generator.hash_mod(&BigInt::from(5), &even),
+            generator.hash_small(5)
+        );
+        assert_eq!(
+            generator.hash_mod(&BigInt::from(6), &even),
+            generator.hash_small(-4)
+        );
+    }
+
+    #[test]
+    fn check_subtractive_with_zero_residue() {
+        let generator = HRPPHICT::new(1000, 256);
+        let power: BigInt = BigInt::from(1) << 400;
+        for (a, b) in [(7, 30), (0, 10), (-20, 1000), (13, -990)] {
+            assert!(generator.check_subtractive(&BigInt::from(a), &BigInt::from(b)));
+        }
+        assert!(generator.check_subtractive(&power, &(&power * BigInt::from(10))));
+
+        let centered = HRPPHICT::new_centered(BigInt::from(500), 1000, 256);
+        assert!(centered.check_additive(&BigInt::from(3), &BigInt::from(40)));
+        assert!(centered.check_subtractive(&BigInt::from(3), &BigInt::from(40)));
+    }
+
+    #[test]
+    fn negating_multiple_of_d_keeps_residue_zero() {
+        let generator = HRPPHICT::new(1000, 256);
+        for x in [0, 10, 990, -1000] {
+            let h = generator.hash_small(x);
+            let negated = generator.zero_hash() - h.clone();
+            let (public, _) = negated.clone().split();
+            assert_eq!(public.r(), 0);
+            assert_eq!(negated, generator.hash_small(-x));
+            // the negated hash keeps combining correctly
+            let sum = negated + generator.hash_small(x + 7);
+            assert_eq!(generator.eval(&sum), (Some(7), true));
+        }
+    }
+
+    #[test]
+    fn capabilities_reflect_known_order() {
+        let full = HRPPHICT::new_safe_prime(100, 128);
+        let all = Capabilities {
+            can_hash: true,
+            can_eval: true,
+            knows_order: true,
+            can_rehash: true,
+        };
+        assert_eq!(full.capabilities(), all);
+
+        let rebuilt = HRPPHICT::from_params(full.params()).unwrap();
+        assert_eq!(
+            rebuilt.capabilities(),
+            Capabilities {
+                knows_order: false,
+                ..all
+            }
+        );
+
+        // the order of a divides lcm(10, 12) = 60, below 2t
+        let (p, q) = (BigUint::from(11u32), BigUint::from(13u32));
+        let small_order = HRPPHICT::from_primes(100, &p, &q).unwrap();
+        assert_eq!(
+            small_order.capabilities(),
+            Capabilities {
+                can_eval: false,
+                can_rehash: false,
+                ..all
+            }
+        );
+
+        // p-1 and q-1 of a real key are too large to factor
+        let (escrowed, _) = HRPPHICT::new_with_escrow(100, 1024);
+        assert!(!escrowed.capabilities().knows_order);
+        assert!(escrowed.capabilities().can_eval);
+
+        let trivial = HRPPHICT::from_params(PublicParams {
+            a: BigUint::from(1u32),
+            ..full.params()
+        })
+        .unwrap();
+        assert_eq!(
+            trivial.capabilities(),
+            Capabilities {
+                can_hash: false,
+                can_eval: false,
+                knows_order: false,
+                can_rehash: false,
+            }
+        );
+    }
+
+    #[test]
+    fn hash_reader_matches_hash_bytes() {
+        let generator = HRPPHICT::new(1000, 256);
+        let bytes: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        for signed in [false, true] {
+            let h = generator.hash_reader(bytes.as_slice(), signed).unwrap();
+            assert_eq!(h, generator.hash_bytes(&bytes, signed));
+        }
+        assert_eq!(
+            generator.hash_bytes(&bytes, false),
+            generator.hash(&BigInt::from_bytes_be(num_bigint::Sign::Plus, &bytes))
+        );
+        assert_eq!(
+            generator.hash_bytes(&[0xff, 0x38], true),
+            generator.hash_small(-200)
+        );
+        assert_eq!(
+            generator.hash_bytes(&[0xff, 0x38], false),
+            generator.hash_small(65336)
+        );
+        for signed in [false, true] {
+            let empty = generator.hash_reader(std::io::empty(), signed).unwrap();
+            assert_eq!(empty, generator.zero_hash());
+        }
+    }
+
+    #[test]
+    fn recover_exponent_with_small_order() {
+        let (p, q) = (BigUint::from(1009u32), BigUint::from(1013u32));
+        let generator = HRPPHICT::from_primes(100, &p, &q).unwrap();
+        let order = generator.randomizer_order().unwrap();
+        let big: BigInt = (BigInt::from(1) << 100) + BigInt::from(12345);
+        for x in [BigInt::zero(), BigInt::from(77), BigInt::from(-5), big] {
+            let h = generator.hash(&x);
+            let expected = x.mod_floor(&BigInt::from(order.clone()));
+            let recovered = generator.recover_exponent_mod_order(&h).unwrap();
+            assert_eq!(BigInt::from(recovered), expected);
+        }
+
+        // an element outside the subgroup of a is not found
+        let other = HRPPHICT::from_params(PublicParams {
+            a: BigUint::from(1u32),
+            ..generator.params()
+        })
+        .unwrap();
+        let foreign = generator.hash_small(3);
+        assert_eq!(other.recover_exponent_mod_order(&foreign), None);
+    }
+
+    #[test]
+    fn recover_exponent_with_escrowed_key() {
+        let (generator, _) = HRPPHICT::new_with_escrow(100, 1024);
+        for x in [0u32, 77, 12345] {
+            let h = generator.hash(&BigInt::from(x));
+            assert_eq!(
+                generator.recover_exponent_mod_order(&h),
+                Some(BigUint::from(x))
+            );
+        }
+    }
+
+    #[test]
+    fn running_commitment_tracks_range() {
+        let generator = HRPPHICT::new(1000, 256);
+        let mut running = RunningCommitment::new(&generator);
+        assert!(running.check_in_range(&generator));
+        for (i, x) in [300, 400, 400, -150, 500, -700].iter().enumerate() {
+            if *x >= 0 {
+                running.add(&generator.hash_small(*x)).unwrap();
+            } else {
+                running.sub(&generator.hash_small(-x)).unwrap();
+            }
+            // checked at intervals: 300, 700, 1100, 950, 1450, 750
+            let expected_in_range = [true, true, false, true, false, true][i];
+            assert_eq!(running.check_in_range(&generator), expected_in_range);
+        }
+        assert_eq!(generator.eval(running.hash()), (Some(750), true));
+
+        let other = HRPPHICT::new(1000, 256);
+        assert!(matches!(
+            running.add(&other.hash_small(1)),
+            Err(Error::ArithmeticMismatch(HashArithError::ParamMismatch))
+        ));
+        assert!(!running.check_in_range(&other));
+        assert_eq!(generator.eval(running.hash()), (Some(750), true));
+    }
+
+    #[test]
+    fn hashing_is_deterministic() {
+        let generator = HRPPHICT::new(1000, 256);
+        assert!(generator.is_deterministic());
+        assert!(HRPPHICT::new_centered(BigInt::from(7), 10, 256).is_deterministic());
+        let x = -(BigInt::from(1) << 500u32);
+        assert_eq!(generator.hash(&x), generator.hash(&x));
+        assert_eq!(
+            generator.hash_salted(&x, b"ctx"),
+            generator.hash_salted(&x, b"ctx")
+        );
+
+        // a generator rebuilt from the params hashes the same
+        let rebuilt = HRPPHICT::from_params(generator.params()).unwrap();
+        assert_eq!(rebuilt.hash(&x), generator.hash(&x));
+        assert!(rebuilt.is_deterministic());
+    }
+
+    #[test]
+    fn blinded_hashing_is_not_deterministic() {
+        let generator = HRPPHICT::new(1000, 256);
+        let mut rng = rand::thread_rng();
+        // blinding with a fresh salt per call, which is_deterministic rules out
+        let mut blinded = |x: &BigInt| generator.hash_salted(x, &rng.gen::<[u8; 16]>());
+        let x = BigInt::from(0x5eed_u32);
+        assert_ne!(blinded(&x), blinded(&x));
+        assert!(generator.is_deterministic());
+    }
+
+    #[test]
+    fn hash_from_exponent_builds_inconsistent_hashes() {
+        let generator = HRPPHICT::new(1000, 256);
+        let consistent = generator.hash_from_exponent(&BigInt::from(-123), 7);
+        assert_eq!(consistent, generator.hash_small(-123));
+
+        let inconsistent = generator.hash_from_exponent(&BigInt::from(-123), 3);
+        assert_eq!(generator.eval(&inconsistent), (None, false));
+        assert!(matches!(
+            generator.eval_unambiguous(&inconsistent),
+            Err(Error::OutOfRange)
+        ));
+        // the group element still carries the exponent
+        let fixed = inconsistent + generator.hash_from_exponent(&BigInt::zero(), 4);
+        assert_eq!(generator.eval(&fixed), (Some(-123), true));
+    }
+
+    #[test]
+    fn eval_at_extreme_parameters() {
+        let base = HRPPHICT::new(u16::MAX, 256);
+        let top = i32::from(u16::MAX);
+        for x in [top, -top, top - 1, -top + 1, 0] {
+            assert_eq!(base.eval(&base.hash_small(x)), (Some(x), true));
+            assert!(base.in_range_ct(&base.hash_small(x)));
+        }
+        assert!(!base.in_range(&base.hash_small(top + 1)));
+
+        // d = t = u16::MAX: residues near d sum beyond u16::MAX
+        let generator = HRPPHICT::from_params(PublicParams {
+            t: u16::MAX,
+            d: u16::MAX,
+            s: 1,
+            ..base.params()
+        })
+        .unwrap();
+        let sum = generator.hash_small(40000) + generator.hash_small(-10000);
+        assert_eq!(generator.eval(&sum), (Some(30000), true));
+        let sum = generator.hash_small(65534) + generator.hash_small(65534);
+        assert_eq!(generator.eval(&sum), (None, false));
+        for x in [top, -top, 65534, 1] {
+            assert_eq!(generator.eval(&generator.hash_small(x)), (Some(x), true));
+            assert!(generator.in_range_ct(&generator.hash_small(x)));
+        }
+    }
+
+    #[test]
+    fn hash_then_eval_matches_eval_of_hash() {
+        let mut rng = rand::thread_rng();
+        let generator = HRPPHICT::new(1000, 256);
+        let centered = HRPPHICT::new_centered(BigInt::from(-5000), 1000, 256);
+        for x in [0, 1000, -1000, 1001, -4321, 5000] {
+            let x = BigInt::from(x);
+            for gen in [&generator, &centered] {
+                assert_eq!(gen.hash_then_eval(&x), gen.eval(&gen.hash(&x)));
+            }
+        }
+        for big in [false, true] {
+            for positive in [false, true] {
+                let x = gen_input(&mut rng, 1000, big, positive, 256);
+                assert_eq!(
+                    generator.hash_then_eval(&x),
+                    generator.eval(&generator.hash(&x))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn aux_residue_is_computed_alongside() {
+        let generator = HRPPHICT::new(1000, 256);
+        let x = BigInt::from(-12345);
+        assert_eq!(generator.hash_extended(&x).aux, None);
+
+        let generator = generator.with_aux_residue(1000);
+        for x in [
+            BigInt::from(-12345),
+            BigInt::from(999),
+            BigInt::from(1) << 300u32,
+        ] {
+            let extended = generator.hash_extended(&x);
+            assert_eq!(extended.hash, generator.hash(&x));
+            let aux = extended.aux.unwrap();
+            assert_eq!(aux.d(), 1000);
+            assert_eq!(BigInt::from(aux.r()), x.mod_floor(&BigInt::from(1000)));
+        }
+        let extended = generator.hash_extended(&BigInt::from(-12345));
+        assert_eq!(extended.aux.unwrap().r(), 655);
+        assert_eq!(generator.eval(&extended.hash), (None, false));
+    }
+
+    #[test]
+    fn compatible_generators_share_d_and_n() {
+        let generator = HRPPHICT::new(1000, 256);
+        assert!(generator.compatible_with(&generator.clone()));
+
+        let mut params = generator.params();
+        params.t = 500;
+        params.s = 50;
+        let same_d_and_n = HRPPHICT::from_params(params.clone()).unwrap();
+        assert!(generator.compatible_with(&same_d_and_n));
+        assert!(same_d_and_n.accepts(&generator.hash_small(3)));
+
+        params.d = 5;
+        params.s = 100;
+        let other_d = HRPPHICT::from_params(params).unwrap();
+        assert!(!generator.compatible_with(&other_d));
+        assert!(!generator.compatible_with(&HRPPHICT::new(1000, 256)));
+    }
+
+    #[test]
+    fn hash_clamped_maps_to_bounds() {
+        let generator = HRPPHICT::new(1000, 256);
+        let huge = BigInt::from(1) << 400u32;
+        for (x, expected) in [
+            (BigInt::from(1001), 1000),
+            (huge.clone(), 1000),
+            (BigInt::from(-1001), -1000),
+            (-huge, -1000),
+            (BigInt::from(-37), -37),
+        ] {
+            let h = generator.hash_clamped(&x);
+            assert_eq!(h, generator.hash_small(expected));
+            assert_eq!(generator.eval(&h), (Some(expected), true));
+        }
+
+        let centered = HRPPHICT::new_centered(BigInt::from(5000), 1000, 256);
+        let h = centered.hash_clamped(&BigInt::zero());
+        assert_eq!(centered.eval(&h), (Some(4000), true));
+    }
+
+    #[test]
+    fn eval_all_batch_matches_sequential_scans() {
+        let generator = HRPPHICT::new(1000, 256);
+        let tiny = tiny_generator(10, 5, 2, 15);
+        for gen in [&generator, &tiny] {
+            let hs: Vec<Hash> = (-12..=12).map(|x| gen.hash_small(x)).collect();
+            let expected: Vec<Vec<i32>> = hs.iter().map(|h| gen.eval_all(h)).collect();
+            assert_eq!(gen.eval_all_batch(&hs), expected);
+        }
+        assert!(generator.eval_all_batch(&[]).is_empty());
+    }
+
+    #[test]
+    fn nonce_from_counter_is_deterministic() {
+        let generator = HRPPHICT::new(1000, 256);
+        let nonce = generator.nonce_from_counter(7);
+        assert_eq!(nonce, generator.nonce_from_counter(7));
+        assert_ne!(nonce, generator.nonce_from_counter(8));
+        assert!(nonce.bits() <= 256 && nonce.sign() != num_bigint::Sign::Minus);
+
+        // a receiver rebuilt from the params derives the same nonce
+        let receiver = HRPPHICT::from_params(generator.params()).unwrap();
+        assert_eq!(receiver.nonce_from_counter(7), nonce);
+        let other = HRPPHICT::new(1000, 256);
+        assert_ne!(other.nonce_from_counter(7), nonce);
+    }
+
+    #[test]
+    fn validate_hash_accepts_well_formed_hashes() {
+        let generator = HRPPHICT::new(1000, 256);
+        for x in [0, 1000, -1000] {
+            assert!(generator.validate_hash(&generator.hash_small(x)).is_ok());
+        }
+        let other = tiny_generator(10, 5, 2, 15);
+        assert!(matches!(
+            generator.validate_hash(&other.hash_small(3)),
+            Err(Error::InvalidHash(ValidationError::ParamMismatch))
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn validate_hash_rejects_malformed_hashes() {
+        let generator = tiny_generator(10, 5, 2, 15);
+        let valid = serde_json::to_value(generator.hash_small(3)).unwrap();
+        let element = |g: u32| serde_json::to_value(BigUint::from(g)).unwrap();
+        for (field, value, expected) in [
+            (
+                "r",
+                serde_json::json!(5),
+                ValidationError::ResidueOutOfRange { r: 5, d: 5 },
+            ),
+            ("g", element(15), ValidationError::ElementOutOfRange),
+            ("g", element(0), ValidationError::ElementOutOfRange),
+        ] {
+            let mut tampered = valid.clone();
+            tampered[field] = value;
+            let h: Hash = serde_json::from_value(tampered).unwrap();
+            assert!(matches!(
+                generator.validate_hash(&h),
+                Err(Error::InvalidHash(e)) if e == expected
+            ));
+        }
+    }
+
+    #[test]
+    fn hash_bits_packs_lsb_first() {
+        let generator = HRPPHICT::new(1000, 256);
+        assert_eq!(
+            generator.hash_bits(&[true, false, true]),
+            generator.hash(&BigInt::from(5))
+        );
+        assert_eq!(
+            generator.hash_bits(&[false, true, false, false]),
+            generator.hash_small(2)
+        );
+        assert_eq!(generator.hash_bits(&[]), generator.zero_hash());
+        let mut wide = vec![false; 300];
+        wide[299] = true;
+        wide[0] = true;
+        let expected: BigInt = (BigInt::from(1) << 299u32) + BigInt::from(1);
+        assert_eq!(generator.hash_bits(&wide), generator.hash(&expected));
+    }
+
+    #[test]
+    fn eval_with_hint_checks_the_guess_first() {
+        // a = 2 has order 4 modulo 15, so -10 and 10 share their hash
+        let tiny = tiny_generator(10, 5, 2, 15);
+        let h = tiny.hash_small(-10);
+        assert_eq!(tiny.eval(&h), (Some(10), true));
+        assert_eq!(tiny.eval_with_hint(&h, -10), (Some(-10), true));
+
+        let generator = HRPPHICT::new(1000, 256);
+        let h = generator.hash_small(-321);
+        assert_eq!(generator.eval_with_hint(&h, -321), (Some(-321), true));
+        for wrong in [-320, -311, 5000, i32::MIN] {
+            assert_eq!(generator.eval_with_hint(&h, wrong), (Some(-321), true));
+        }
+        let out = generator.hash_small(1001);
+        assert_eq!(generator.eval_with_hint(&out, 1001), (None, false));
+
+        let centered = HRPPHICT::new_centered(BigInt::from(100), 10, 256);
+        let h = centered.hash_small(95);
+        assert_eq!(centered.eval_with_hint(&h, 95), (Some(95), true));
+        assert_eq!(centered.eval_with_hint(&h, 96), (Some(95), true));
+    }
+
+    #[test]
+    fn hash_neg_matches_hash_of_negation() {
+        let mut rng = rand::thread_rng();
+        let generator = HRPPHICT::new(1000, 256);
+        let centered = HRPPHICT::new_centered(BigInt::from(30), 1000, 256);
+        for big in [false, true] {
+            for positive in [false, true] {
+                let x = gen_input(&mut rng, 1000, big, positive, 256);
+                assert_eq!(generator.hash_neg(&x), generator.hash(&-&x));
+                assert_eq!(centered.hash_neg(&x), centered.hash(&-&x));
+            }
+        }
+        for x in [0, 10, 1000] {
+            let x = BigInt::from(x);
+            assert_eq!(generator.hash_neg(&x), generator.hash(&-&x));
+        }
+    }
+
+    #[test]
+    fn hash_chunked_matches_hash_batch() {
+        let generator = HRPPHICT::new(1000, 256);
+        let xs: Vec<BigInt> = (-25..25).map(|x| BigInt::from(x) << 100u32).collect();
+        let expected = generator.hash_batch(&xs);
+        assert_eq!(
+            expected,
+            xs.iter().map(|x| generator.hash(x)).collect::<Vec<_>>()
+        );
+
+        for chunk_size in [1, 7, 50, 64] {
+            let mut sizes = Vec::new();
+            let mut out = Vec::new();
+            generator.hash_chunked(xs.clone().into_iter(), chunk_size, |chunk| {
+                sizes.push(chunk.len());
+                out.extend(chunk);
+            });
+            assert_eq!(out, expected);
+            assert!(sizes.iter().all(|s| *s <= chunk_size));
+            assert_eq!(sizes.len(), xs.len().div_ceil(chunk_size));
+        }
+
+        let mut calls = 0;
+        generator.hash_chunked(std::iter::empty(), 8, |_| calls += 1);
+        assert_eq!(calls, 0);
+    }
+
+    #[test]
+    fn openings_disclose_selected_values() {
+        let generator = HRPPHICT::new(1000, 256);
+        let values: Vec<BigInt> = [17, -4000, 123456].into_iter().map(BigInt::from).collect();
+        let hashes: Vec<Hash> = values.iter().map(|x| generator.hash(x)).collect();
+
+        // disclose only the second value
+        let opening = generator.open(&hashes[1], &values[1]).unwrap();
+        assert_eq!(opening.value, values[1]);
+        assert!(generator.check_opening(&hashes[1], &opening));
+        assert!(!generator.check_opening(&hashes[0], &opening));
+
+        assert!(matches!(
+            generator.open(&hashes[2], &values[0]),
+            Err(Error::InvalidOpening)
+        ));
+        let forged = Opening {
+            value: &values[1] + BigInt::from(10),
+        };
+        assert!(!generator.check_opening(&hashes[1], &forged));
+    }
+
+    #[test]
+    fn distinct_residues_counts_classes() {
+        let generator = HRPPHICT::new(1000, 256);
+        // residues 3, 3, 7, 0, 7, 9
+        let hs: Vec<Hash> = [3, 13, -3, 1000, 7, 123_456_789]
+            .iter()
+            .map(|x| generator.hash(&BigInt::from(*x)))
+            .collect();
+        assert_eq!(generator.distinct_residues(&hs), 4);
+        assert_eq!(generator.distinct_residues(&hs[..2]), 1);
+        assert_eq!(generator.distinct_residues(&[]), 0);
+    }
+
     /// Test cases with positive small inputs
+    #[allow(clippy::useless_conversion)]
     fn test_eval_small(lambda: u64, positive: bool, big: bool) {
         let mut rng = rand::thread_rng();
         let t: u16 = rng.gen();
@@ -110,6 +2490,23 @@ mod tests {
         }
     }
 
+    /// Generator with tiny parameters for hand-checked cases
+    fn tiny_generator(t: u16, d: u16, a: u32, n: u32) -> HRPPHICT {
+        HRPPHICT::from_params(PublicParams {
+            t,
+            d,
+            s: t / d,
+            a: BigUint::from(a),
+            n: BigUint::from(n),
+        })
+        .unwrap()
+    }
+
+    #[allow(
+        clippy::useless_conversion,
+        clippy::assign_op_pattern,
+        clippy::needless_return
+    )]
     fn gen_input(rng: &mut ThreadRng, t: u16, big: bool, positive: bool, lambda: u64) -> BigInt {
         let positive_result = if big {
             let big_t = BigUint::from(t);